@@ -1,266 +1,487 @@
+use std::sync::Arc;
+
 use glam::Vec2;
 use hecs::World;
 use papercut::{
     components::{Drawable, Transform},
     graphics::{
-        CircleShape, Color, Geometry, LineShape, PolygonShape, RectangleShape, Tessellator,
+        BooleanOp, CircleShape, Color, Fill, FillRule, Geometry, LineCap, LineJoin, LineShape,
+        PathBuilder, PathShape, PolygonShape, RectangleShape, Tessellator,
     },
-    input::KeyCode,
-    Context, Fullscreen, RendererConfig, Scene, WindowConfig,
+    input::{KeyCode, MouseButton},
+    material::{Material, MaterialHandle, MaterialInstance},
+    Context, GeometryError, RendererConfig, Scene, WindowConfig,
 };
 
-fn main() {
-    let mut wc = WindowConfig::default();
-    wc.fullscreen = None;
+fn main() -> Result<(), papercut::PapercutError> {
+    let wc = WindowConfig {
+        fullscreen: None,
+        ..Default::default()
+    };
     let rc = RendererConfig::default();
 
     papercut::init_logger();
-    papercut::start::<GeometryExample>(wc, rc);
+    papercut::start::<GeometryExample>(wc, rc)
 }
 
 #[derive(Default)]
 struct GeometryExample {
     world: World,
+    running_time: f32,
 }
 
-impl papercut::Game for GeometryExample {
-    fn on_create(&mut self) {
-        let mut world = World::new();
-
-        let tolerance = 0.02;
-        let mut tessellator = Tessellator::new(tolerance);
-
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(200.0, 200.0);
-        transform.origin = Vec2::new(100.0, 100.0);
+impl GeometryExample {
+    fn spawn_shapes(world: &mut World, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        let transform = Transform {
+            translation: Vec2::new(200.0, 200.0),
+            origin: Vec2::new(100.0, 100.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(200.0, 200.0);
-        rect.fill_color = Color::WHITE;
+        rect.fill = Color::WHITE.into();
         rect.outline_thickness = 1.0;
         rect.outline_color = Color::BLACK;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(200.0, 200.0);
-        transform.rotation = 30.0;
-        transform.origin = Vec2::new(100.0, 100.0);
+        let transform = Transform {
+            translation: Vec2::new(200.0, 200.0),
+            rotation: 30.0,
+            origin: Vec2::new(100.0, 100.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(200.0, 200.0);
-        rect.fill_color = Color::WHITE;
+        rect.fill = Color::WHITE.into();
         rect.outline_thickness = 1.0;
         rect.outline_color = Color::BLACK;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(400.0, 400.0);
+        let transform = Transform {
+            translation: Vec2::new(400.0, 400.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(300.0, 150.0);
-        rect.fill_color = Color::BLACK;
+        rect.fill = Color::BLACK.into();
         rect.outline_thickness = 5.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
         // Bottom left
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(400.0, 405.0);
+        let transform = Transform {
+            translation: Vec2::new(400.0, 405.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(1.0, 0.0, 0.0, 1.0);
+        rect.fill = Color::new(1.0, 0.0, 0.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(405.0, 400.0);
+        let transform = Transform {
+            translation: Vec2::new(405.0, 400.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(0.0, 0.0, 1.0, 1.0);
+        rect.fill = Color::new(0.0, 0.0, 1.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(405.0, 405.0);
+        let transform = Transform {
+            translation: Vec2::new(405.0, 405.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(0.0, 1.0, 0.0, 1.0);
+        rect.fill = Color::new(0.0, 1.0, 0.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
         // Top left
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(400.0, 540.0);
+        let transform = Transform {
+            translation: Vec2::new(400.0, 540.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(1.0, 0.0, 0.0, 1.0);
+        rect.fill = Color::new(1.0, 0.0, 0.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(405.0, 545.0);
+        let transform = Transform {
+            translation: Vec2::new(405.0, 545.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(0.0, 0.0, 1.0, 1.0);
+        rect.fill = Color::new(0.0, 0.0, 1.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(405.0, 540.0);
+        let transform = Transform {
+            translation: Vec2::new(405.0, 540.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(0.0, 1.0, 0.0, 1.0);
+        rect.fill = Color::new(0.0, 1.0, 0.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
         // Bottom right
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(695.0, 405.0);
+        let transform = Transform {
+            translation: Vec2::new(695.0, 405.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(1.0, 0.0, 0.0, 1.0);
+        rect.fill = Color::new(1.0, 0.0, 0.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(690.0, 400.0);
+        let transform = Transform {
+            translation: Vec2::new(690.0, 400.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(0.0, 0.0, 1.0, 1.0);
+        rect.fill = Color::new(0.0, 0.0, 1.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(690.0, 405.0);
+        let transform = Transform {
+            translation: Vec2::new(690.0, 405.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(0.0, 1.0, 0.0, 1.0);
+        rect.fill = Color::new(0.0, 1.0, 0.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
         // Top right
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(695.0, 540.0);
+        let transform = Transform {
+            translation: Vec2::new(695.0, 540.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(1.0, 0.0, 0.0, 1.0);
+        rect.fill = Color::new(1.0, 0.0, 0.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(690.0, 545.0);
+        let transform = Transform {
+            translation: Vec2::new(690.0, 545.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(0.0, 0.0, 1.0, 1.0);
+        rect.fill = Color::new(0.0, 0.0, 1.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(690.0, 540.0);
+        let transform = Transform {
+            translation: Vec2::new(690.0, 540.0),
+            ..Default::default()
+        };
         let mut rect = RectangleShape::default();
         rect.size = Vec2::new(5.0, 5.0);
-        rect.fill_color = Color::new(0.0, 1.0, 0.0, 1.0);
+        rect.fill = Color::new(0.0, 1.0, 0.0, 1.0).into();
         rect.outline_thickness = 0.0;
         rect.outline_color = Color::WHITE;
-        rect.update(&mut tessellator);
+        rect.update(tessellator)?;
         let drawable = Drawable::Rect(rect);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(400.0, 100.0);
-        transform.origin = Vec2::new(100.0, 100.0);
+        let transform = Transform {
+            translation: Vec2::new(400.0, 100.0),
+            origin: Vec2::new(100.0, 100.0),
+            ..Default::default()
+        };
         let mut circle = CircleShape::default();
         circle.radius = 100.0;
-        circle.fill_color = Color::new(0.0, 0.0, 1.0, 1.0);
+        circle.fill = Color::new(0.0, 0.0, 1.0, 1.0).into();
         circle.outline_thickness = 10.0;
         circle.outline_color = Color::new(1.0, 1.0, 0.0, 1.0);
-        circle.update(&mut tessellator);
+        let pulse = Material::new("pulse", include_str!("pulse.wgsl"));
+        circle.material = Some(MaterialInstance::new(MaterialHandle::new(pulse)));
+        circle.update(tessellator)?;
         let drawable = Drawable::Circle(circle);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(120.0, 450.0);
-        transform.origin = Vec2::new(100.0, 100.0);
+        let transform = Transform {
+            translation: Vec2::new(120.0, 450.0),
+            origin: Vec2::new(100.0, 100.0),
+            ..Default::default()
+        };
         let mut polygon = PolygonShape::default();
         polygon.radius = 100.0;
         polygon.point_count = 5;
-        polygon.fill_color = Color::new(0.0, 1.0, 0.0, 1.0);
+        polygon.fill = Color::new(0.0, 1.0, 0.0, 1.0).into();
         polygon.outline_thickness = 10.0;
         polygon.outline_color = Color::new(1.0, 0.0, 0.0, 1.0);
-        polygon.update(&mut tessellator);
+        polygon.update(tessellator)?;
         let drawable = Drawable::Polygon(polygon);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(900.0, 550.0);
-        transform.rotation = 90.0;
+        // A dashed selection box around the pentagon.
+        let transform = Transform {
+            translation: Vec2::new(15.0, 345.0),
+            z_index: 1.0,
+            ..Default::default()
+        };
+        let mut rect = RectangleShape::default();
+        rect.size = Vec2::new(210.0, 210.0);
+        rect.fill = Color::new(0.0, 0.0, 0.0, 0.0).into();
+        rect.outline_thickness = 2.0;
+        rect.outline_color = Color::WHITE;
+        rect.outline_style.dashes = vec![8.0, 6.0];
+        rect.update(tessellator)?;
+        world.spawn((transform, Drawable::Rect(rect)));
+
+        let transform = Transform {
+            translation: Vec2::new(900.0, 550.0),
+            rotation: 90.0,
+            ..Default::default()
+        };
         let mut polygon = PolygonShape::default();
         polygon.radius = 50.0;
         polygon.point_count = 3;
-        polygon.fill_color = Color::new(0.0, 1.0, 0.0, 1.0);
+        polygon.fill = Color::new(0.0, 1.0, 0.0, 1.0).into();
         polygon.outline_thickness = 2.0;
         polygon.outline_color = Color::new(1.0, 0.0, 0.0, 1.0);
-        polygon.update(&mut tessellator);
+        polygon.update(tessellator)?;
         let drawable = Drawable::Polygon(polygon);
         world.spawn((transform, drawable));
 
-        let mut transform = Transform::default();
-        transform.translation = Vec2::new(400.0, 100.0);
+        let transform = Transform {
+            translation: Vec2::new(740.0, 260.0),
+            ..Default::default()
+        };
+        let mut rect = RectangleShape::default();
+        rect.size = Vec2::new(200.0, 100.0);
+        rect.fill = Fill::LinearGradient {
+            start: Vec2::ZERO,
+            end: Vec2::new(200.0, 0.0),
+            start_color: Color::new(1.0, 0.5, 0.0, 1.0),
+            end_color: Color::new(0.5, 0.0, 1.0, 1.0),
+        };
+        rect.update(tessellator)?;
+        world.spawn((transform, Drawable::Rect(rect)));
+
+        let transform = Transform {
+            translation: Vec2::new(780.0, 130.0),
+            ..Default::default()
+        };
+        let mut circle = CircleShape::default();
+        circle.radius = 60.0;
+        circle.fill = Fill::Custom(Arc::new(|position| {
+            let wave = (position.x / 12.0).sin() * (position.y / 12.0).cos();
+            Color::new(0.5 + 0.5 * wave, 0.2, 0.5 - 0.5 * wave, 1.0)
+        }));
+        circle.update(tessellator)?;
+        world.spawn((transform, Drawable::Circle(circle)));
+
+        let mut star = PathBuilder::new();
+        for i in 0..10 {
+            let radius = if i % 2 == 0 { 60.0 } else { 25.0 };
+            let angle = (90.0 + i as f32 * 36.0_f32).to_radians();
+            let point = Vec2::new(60.0, 60.0) + Vec2::new(angle.cos(), angle.sin()) * radius;
+            if i == 0 {
+                star.move_to(point);
+            } else {
+                star.line_to(point);
+            }
+        }
+        star.close();
+        let transform = Transform {
+            translation: Vec2::new(740.0, 0.0),
+            ..Default::default()
+        };
+        let mut path = PathShape::default();
+        path.path = star.build();
+        path.fill = Color::new(1.0, 0.8, 0.0, 1.0).into();
+        path.outline_thickness = 3.0;
+        path.outline_color = Color::new(1.0, 0.4, 0.0, 1.0);
+        path.outline_style.join = LineJoin::Round;
+        path.update(tessellator)?;
+        world.spawn((transform, Drawable::Path(path)));
+
+        let mut ship = PathBuilder::new();
+        ship.move_to(Vec2::new(0.0, 0.0))
+            .cubic_bezier_to(
+                Vec2::new(20.0, 60.0),
+                Vec2::new(40.0, 100.0),
+                Vec2::new(60.0, 120.0),
+            )
+            .cubic_bezier_to(
+                Vec2::new(80.0, 100.0),
+                Vec2::new(100.0, 60.0),
+                Vec2::new(120.0, 0.0),
+            )
+            .quadratic_bezier_to(Vec2::new(60.0, 30.0), Vec2::new(0.0, 0.0))
+            .close()
+            .move_to(Vec2::new(70.0, 60.0))
+            .arc(Vec2::new(60.0, 60.0), Vec2::new(10.0, 10.0), 360.0, 0.0)
+            .close();
+        let transform = Transform {
+            translation: Vec2::new(880.0, 0.0),
+            ..Default::default()
+        };
+        let mut path = PathShape::default();
+        path.path = ship.build();
+        path.fill_rule = FillRule::EvenOdd;
+        path.fill = Color::new(0.6, 0.6, 0.7, 1.0).into();
+        path.outline_thickness = 2.0;
+        path.outline_color = Color::WHITE;
+        path.update(tessellator)?;
+        world.spawn((transform, Drawable::Path(path)));
+
+        let mut plate = PathBuilder::new();
+        plate.rectangle(Vec2::ZERO, Vec2::new(240.0, 100.0));
+        let mut holes = PathBuilder::new();
+        holes
+            .circle(Vec2::new(60.0, 50.0), 30.0)
+            .circle(Vec2::new(180.0, 50.0), 30.0)
+            .circle(Vec2::new(120.0, 100.0), 25.0);
+        let transform = Transform {
+            translation: Vec2::new(740.0, 160.0),
+            ..Default::default()
+        };
+        let mut path = PathShape::default();
+        path.path = plate.build();
+        path.fill = Color::new(0.3, 0.7, 0.4, 1.0).into();
+        path.outline_thickness = 2.0;
+        path.outline_color = Color::WHITE;
+        let mut cutter = PathShape::default();
+        cutter.path = holes.build();
+        let mut path = path.boolean(&cutter, BooleanOp::Difference);
+        path.update(tessellator)?;
+        world.spawn((transform, Drawable::Path(path)));
+
+        let transform = Transform {
+            translation: Vec2::new(400.0, 100.0),
+            ..Default::default()
+        };
         let mut line = LineShape::default();
         line.length = 100.0;
         line.angle = 60.0;
         line.outline_thickness = 10.0;
         line.outline_color = Color::new(1.0, 1.0, 0.0, 1.0);
-        line.update(&mut tessellator);
+        line.outline_style.cap = LineCap::Round;
+        line.update(tessellator)?;
         let drawable = Drawable::Line(line);
         world.spawn((transform, drawable));
 
+        Ok(())
+    }
+}
+
+impl papercut::Game for GeometryExample {
+    fn on_create(&mut self) {
+        let mut world = World::new();
+
+        let tolerance = 0.02;
+        let mut tessellator = Tessellator::new(tolerance);
+        Self::spawn_shapes(&mut world, &mut tessellator).expect("example shapes should be valid");
+
         self.world = world;
     }
 
     fn on_update(
         &mut self,
         input: &papercut::input::InputHelper,
-        _ctx: &mut Context,
-        _camera: &papercut::camera::Camera,
-        _dt: std::time::Duration,
+        ctx: &mut Context,
+        camera: &papercut::camera::Camera,
+        dt: std::time::Duration,
     ) -> bool {
+        if input.mouse_pressed(MouseButton::Left) {
+            let mouse = input.mouse_in_world(camera);
+            let picked = self
+                .world
+                .query::<(&Transform, &Drawable)>()
+                .iter()
+                .filter(|(_id, (transform, drawable))| drawable.contains_point(mouse, transform))
+                .max_by(|(_, (a, _)), (_, (b, _))| a.z_index.total_cmp(&b.z_index))
+                .and_then(|(_id, (transform, drawable))| drawable.world_bounds(transform));
+            match picked {
+                Some(bounds) => ctx.set_window_title(format!(
+                    "Picked shape at {} of size {}",
+                    bounds.position, bounds.size
+                )),
+                None => ctx.set_window_title("Nothing picked"),
+            }
+        }
+
+        if input.key_pressed(KeyCode::M) {
+            let sample_count = if ctx.sample_count() > 1 { 1 } else { 4 };
+            ctx.set_sample_count(sample_count);
+        }
+
+        if input.key_pressed(KeyCode::F12) {
+            ctx.request_screenshot("screenshots/geometry.png");
+        }
+
+        if input.key_pressed(KeyCode::F11) {
+            if ctx.is_capturing() {
+                ctx.stop_capture();
+            } else {
+                ctx.start_capture("screenshots/capture", 60);
+            }
+        }
+
+        self.running_time += dt.as_secs_f32();
+        for (_id, drawable) in self.world.query_mut::<&mut Drawable>() {
+            if let Drawable::Circle(CircleShape {
+                material: Some(material),
+                ..
+            }) = drawable
+            {
+                material.params[0].x = self.running_time;
+            }
+        }
+
         !input.quit() && !input.key_pressed(KeyCode::Escape)
     }
 
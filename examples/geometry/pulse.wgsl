@@ -0,0 +1,43 @@
+// Vertex
+
+struct Globals {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+struct MaterialParams {
+    // params[0].x: time in seconds.
+    params: array<vec4<f32>, 4>,
+};
+
+@group(1) @binding(0) var<uniform> material: MaterialParams;
+
+struct VertexOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec4<f32>,
+    @location(1) color: vec4<f32>,
+) -> VertexOutput {
+    var clip_position = globals.projection * globals.view * position;
+
+    return VertexOutput(color, clip_position);
+}
+
+
+// Fragment
+
+struct Output {
+    @location(0) color: vec4<f32>,
+};
+
+@fragment
+fn fs_main(@location(0) color: vec4<f32>) -> Output {
+    let pulse = 0.75 + 0.25 * sin(material.params[0].x * 4.0);
+    return Output(vec4<f32>(color.rgb * pulse, color.a));
+}
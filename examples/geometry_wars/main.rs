@@ -5,12 +5,15 @@ use std::{
 };
 
 use glam::Vec2;
-use hecs::{EntityBuilder, With, World};
+use hecs::{EntityBuilder, World};
 use papercut::{
     camera::Camera,
     components::{Drawable, Tag, Transform},
-    graphics::{Color, Geometry, PolygonShape, Tessellator},
+    graphics::{BlendMode, Color, Fill, Geometry, PolygonShape, Tessellator},
     input::{InputHelper, KeyCode, MouseButton},
+    particles::{update_particles, ParticleEmitter},
+    post_processing::PostEffect,
+    tween::{update_tweens, Easing, Repeat, Tween, TweenProperty, Tweens},
     Context, Game, RendererConfig, Scene, WindowConfig,
 };
 use rand::{thread_rng, Rng};
@@ -21,7 +24,7 @@ const SMALL_ENEMY_TAG: &str = "small_enemy";
 const BULLET_TAG: &str = "bullet";
 const SPECIAL_WEAPON_TAG: &str = "special_weapon";
 
-fn main() {
+fn main() -> Result<(), papercut::PapercutError> {
     let wc = WindowConfig {
         title: "Geometry Wars".to_string(),
         size: Vec2::new(1280.0, 720.0),
@@ -30,10 +33,25 @@ fn main() {
     };
 
     let clear_color = Color::new(0.0, 0.0, 0.0, 1.0);
-    let rc = RendererConfig { clear_color };
+    let post_effects = vec![
+        PostEffect::Bloom {
+            threshold: 0.6,
+            intensity: 1.5,
+            radius: 12.0,
+        },
+        PostEffect::Vignette {
+            intensity: 0.4,
+            smoothness: 0.6,
+        },
+    ];
+    let rc = RendererConfig {
+        clear_color,
+        post_effects,
+        ..Default::default()
+    };
 
     papercut::init_logger();
-    papercut::start::<GeometryWars>(wc, rc);
+    papercut::start::<GeometryWars>(wc, rc)
 }
 
 #[derive(Default)]
@@ -49,6 +67,8 @@ struct GeometryWars {
     enemy_config: EnemyConfig,
     bullet_config: BulletConfig,
     world: World,
+    /// Kept between updates so identical enemies and bullets share their meshes.
+    tessellator: Option<Tessellator>,
 }
 
 impl Game for GeometryWars {
@@ -110,7 +130,10 @@ impl Game for GeometryWars {
         dt: Duration,
     ) -> bool {
         // TODO: Add dirty flag to shape/drawable and move tesselator behind the scenes.
-        let mut tessellator = Tessellator::new(0.02);
+        let mut tessellator = self
+            .tessellator
+            .take()
+            .unwrap_or_else(|| Tessellator::new(0.02));
         self.system_user_input(input, camera);
 
         if !self.paused {
@@ -119,14 +142,16 @@ impl Game for GeometryWars {
             self.system_bullet_spawner(&mut tessellator);
             self.system_special_weapon_spawner(&mut tessellator);
             self.system_movement(ctx.window_size(), dt);
-            self.system_lifespan(&mut tessellator, dt);
+            self.system_lifespan(dt);
             self.system_collision();
             self.system_small_enemy_spawner(&mut tessellator);
+            update_tweens(&mut self.world, &mut tessellator, dt);
+            update_particles(&mut self.world, dt);
 
             self.running_time += dt; // TODO: Running time should be provided by the engine.
         }
+        self.tessellator = Some(tessellator);
 
-        self.system_rotate_visible_entities(dt);
         self.system_remove_dead_entities();
 
         ctx.set_window_title(format!("Geometry Wars - Score: {}", self.score));
@@ -224,7 +249,7 @@ impl GeometryWars {
             .query::<(&Tag, &Drawable, &Transform, &Physics, &Health, &Score)>()
             .iter()
         {
-            if tag.name == ENEMY_TAG && health.health <= 0 {
+            if tag.name == ENEMY_TAG && health.health == 0 {
                 if let Drawable::Polygon(parent_shape) = &shape {
                     build_small_enemies(
                         &mut to_spawn,
@@ -278,18 +303,13 @@ impl GeometryWars {
             self.world.query_mut::<(&Input, &Transform, &Drawable)>()
         {
             let parent_position = transform.translation;
-            if input.right_button {
-                if self.last_special_weapon_spawn_time + respawn_interval < self.running_time {
-                    if let Drawable::Polygon(parent_shape) = drawable {
-                        build_special_weapon(
-                            &mut to_spawn,
-                            parent_position,
-                            parent_shape,
-                            tessellator,
-                        );
-                    }
-                    self.last_special_weapon_spawn_time = self.running_time;
+            if input.right_button
+                && self.last_special_weapon_spawn_time + respawn_interval < self.running_time
+            {
+                if let Drawable::Polygon(parent_shape) = drawable {
+                    build_special_weapon(&mut to_spawn, parent_position, parent_shape, tessellator);
                 }
+                self.last_special_weapon_spawn_time = self.running_time;
             }
         }
 
@@ -322,7 +342,7 @@ impl GeometryWars {
 
                 let move_dir = move_dir.normalize_or_zero();
 
-                future_pos = future_pos + (move_dir * physics.velocity) * dt.as_secs_f32();
+                future_pos += (move_dir * physics.velocity) * dt.as_secs_f32();
 
                 if let Drawable::Polygon(shape) = drawable {
                     if future_pos.x - shape.radius < 0.0 {
@@ -339,7 +359,7 @@ impl GeometryWars {
                     }
                 }
             } else {
-                future_pos = future_pos + physics.velocity * dt.as_secs_f32();
+                future_pos += physics.velocity * dt.as_secs_f32();
 
                 if let Drawable::Polygon(shape) = drawable {
                     if future_pos.x - shape.radius < 0.0 {
@@ -365,28 +385,9 @@ impl GeometryWars {
         }
     }
 
-    fn system_lifespan(&mut self, tessellator: &mut Tessellator, dt: Duration) {
-        for (_id, (lifespan, drawable, tag)) in self
-            .world
-            .query_mut::<(&mut Lifespan, &mut Drawable, &Tag)>()
-        {
-            if tag.name == SMALL_ENEMY_TAG
-                || tag.name == BULLET_TAG
-                || tag.name == SPECIAL_WEAPON_TAG
-            {
-                lifespan.remaining = lifespan.remaining.saturating_sub(dt);
-                if lifespan.remaining > Duration::ZERO {
-                    let alpha_ratio =
-                        lifespan.remaining.as_secs_f32() / lifespan.total.as_secs_f32();
-
-                    if let Drawable::Polygon(shape) = drawable {
-                        let new_alpha = 1.0 * alpha_ratio;
-                        shape.fill_color.a = new_alpha;
-                        shape.outline_color.a = new_alpha;
-                        shape.update(tessellator);
-                    }
-                }
-            }
+    fn system_lifespan(&mut self, dt: Duration) {
+        for (_id, lifespan) in self.world.query_mut::<&mut Lifespan>() {
+            lifespan.remaining = lifespan.remaining.saturating_sub(dt);
         }
     }
 
@@ -471,18 +472,19 @@ impl GeometryWars {
         self.score = total_score;
     }
 
-    fn system_rotate_visible_entities(&mut self, dt: Duration) {
-        for (_id, (transform,)) in self.world.query_mut::<With<(&mut Transform,), &Drawable>>() {
-            transform.rotation += 60.0 * dt.as_secs_f32();
-        }
-    }
-
     fn system_remove_dead_entities(&mut self) {
         let mut to_remove = HashSet::new();
+        let mut explosions = Vec::new();
 
-        for (id, (lifespan, health)) in self
+        for (id, (lifespan, health, emitter, transform, drawable)) in self
             .world
-            .query::<(Option<&Lifespan>, Option<&Health>)>()
+            .query::<(
+                Option<&Lifespan>,
+                Option<&Health>,
+                Option<&ParticleEmitter>,
+                Option<&Transform>,
+                Option<&Drawable>,
+            )>()
             .iter()
         {
             if let Some(lifespan) = lifespan {
@@ -491,7 +493,16 @@ impl GeometryWars {
                 }
             }
             if let Some(health) = health {
-                if health.health <= 0 {
+                if health.health == 0 {
+                    to_remove.insert(id);
+                    if let (Some(transform), Some(Drawable::Polygon(shape))) = (transform, drawable)
+                    {
+                        explosions.push((transform.translation, enemy_color(&shape.fill)));
+                    }
+                }
+            }
+            if let Some(emitter) = emitter {
+                if emitter.is_finished() {
                     to_remove.insert(id);
                 }
             }
@@ -500,12 +511,21 @@ impl GeometryWars {
         for entity in to_remove.into_iter() {
             self.world.despawn(entity).expect("TODO: error handling");
         }
+
+        for (position, color) in explosions {
+            self.world.spawn(build_explosion(position, color));
+        }
     }
 
     fn system_render(&self, ctx: &mut Context, scene: &mut Scene) {
         for (_id, (transform, drawable)) in self.world.query::<(&Transform, &Drawable)>().iter() {
             ctx.draw_shape(transform, drawable, scene);
         }
+
+        let mut query = self.world.query::<(&Transform, &ParticleEmitter)>();
+        for (_id, (transform, emitter)) in query.iter() {
+            ctx.draw_particles(transform, emitter, scene);
+        }
     }
 }
 
@@ -517,6 +537,7 @@ fn check_collision(a: &(&Transform, &CircleCollider), b: &(&Transform, &CircleCo
     actual_distance <= min_distance
 }
 
+#[allow(dead_code)] // TODO: Render text once fonts are supported.
 #[derive(Debug, Default)]
 struct FontConfig {
     file: String,
@@ -609,34 +630,44 @@ fn build_player(
         name: PLAYER_TAG.to_string(),
     };
 
-    let mut transform = Transform::default();
-    transform.translation = Vec2::new(window_size.x / 2.0, window_size.y / 2.0);
-    transform.origin = Vec2::new(
-        player_config.shape_radius as f32,
-        player_config.shape_radius as f32,
-    );
+    let transform = Transform {
+        translation: Vec2::new(window_size.x / 2.0, window_size.y / 2.0),
+        origin: Vec2::new(
+            player_config.shape_radius as f32,
+            player_config.shape_radius as f32,
+        ),
+        ..Default::default()
+    };
 
     let mut shape = PolygonShape::default();
     shape.radius = player_config.shape_radius as f32;
     shape.point_count = player_config.vertices;
-    shape.fill_color = player_config.fill_color;
+    shape.fill = player_config.fill_color.into();
     shape.outline_color = player_config.outline_color;
     shape.outline_thickness = player_config.outline_thicknes as f32;
-    shape.update(tessellator);
+    if let Err(e) = shape.update(tessellator) {
+        log::warn!("{}", e);
+    }
 
     let drawable = Drawable::Polygon(shape);
 
-    let mut collider = CircleCollider::default();
-    collider.radius = player_config.collision_radius as f32;
+    let collider = CircleCollider {
+        radius: player_config.collision_radius as f32,
+    };
 
-    let mut physics = Physics::default();
-    physics.velocity = Vec2::new(player_config.speed, player_config.speed);
+    let physics = Physics {
+        velocity: Vec2::new(player_config.speed, player_config.speed),
+    };
 
     let input = Input::default();
 
     let health = Health { health: 1 };
 
-    eb.add_bundle((tag, transform, drawable, collider, physics, input, health));
+    let tweens = Tweens::from(spin());
+
+    eb.add_bundle((
+        tag, transform, drawable, collider, physics, input, health, tweens,
+    ));
 }
 
 fn build_enemy(
@@ -659,12 +690,14 @@ fn build_enemy(
     let pos_y = rng.gen_range(y_range);
     let enemy_position = Vec2::new(pos_x, pos_y);
 
-    let mut transform = Transform::default();
-    transform.translation = enemy_position;
-    transform.origin = Vec2::new(
-        enemy_config.shape_radius as f32,
-        enemy_config.shape_radius as f32,
-    );
+    let transform = Transform {
+        translation: enemy_position,
+        origin: Vec2::new(
+            enemy_config.shape_radius as f32,
+            enemy_config.shape_radius as f32,
+        ),
+        ..Default::default()
+    };
 
     let vertex_count = rng.gen_range(enemy_config.min_vertices..=enemy_config.max_vertices);
     let r = rng.gen_range(0.0_f32..=1.0_f32);
@@ -675,15 +708,18 @@ fn build_enemy(
     let mut shape = PolygonShape::default();
     shape.radius = enemy_config.shape_radius as f32;
     shape.point_count = vertex_count;
-    shape.fill_color = fill_color;
+    shape.fill = enemy_fill(fill_color, shape.radius);
     shape.outline_color = enemy_config.outline_color;
     shape.outline_thickness = enemy_config.outline_thicknes as f32;
-    shape.update(tessellator);
+    if let Err(e) = shape.update(tessellator) {
+        log::warn!("{}", e);
+    }
 
     let drawable = Drawable::Polygon(shape);
 
-    let mut collider = CircleCollider::default();
-    collider.radius = enemy_config.collision_radius as f32;
+    let collider = CircleCollider {
+        radius: enemy_config.collision_radius as f32,
+    };
 
     let mut speed_x = rng.gen_range(enemy_config.min_speed..=enemy_config.max_speed);
     if rng.gen_bool(0.5) {
@@ -695,8 +731,9 @@ fn build_enemy(
     }
     let enemy_speed = Vec2::new(speed_x, speed_y);
 
-    let mut physics = Physics::default();
-    physics.velocity = enemy_speed;
+    let physics = Physics {
+        velocity: enemy_speed,
+    };
 
     let health = Health { health: 1 };
 
@@ -704,7 +741,11 @@ fn build_enemy(
         score: vertex_count * 100,
     };
 
-    eb.add_bundle((tag, transform, drawable, collider, physics, health, score));
+    let tweens = Tweens::from(spin());
+
+    eb.add_bundle((
+        tag, transform, drawable, collider, physics, health, score, tweens,
+    ));
 }
 
 fn build_small_enemies(
@@ -719,7 +760,7 @@ fn build_small_enemies(
     let position = parent_position;
     let speed = parent_physics.velocity;
     let radius = parent_shape.radius / 2.0;
-    let fill_color = parent_shape.fill_color;
+    let fill_color = enemy_color(&parent_shape.fill);
     let outline_color = parent_shape.outline_color;
     let outline_thickness = parent_shape.outline_thickness;
     let point_count = parent_shape.point_count;
@@ -737,19 +778,21 @@ fn build_small_enemies(
         let mut shape = PolygonShape::default();
         shape.radius = radius;
         shape.point_count = point_count;
-        shape.fill_color = fill_color;
+        shape.fill = enemy_fill(fill_color, radius);
         shape.outline_color = outline_color;
         shape.outline_thickness = outline_thickness;
-        shape.update(tessellator);
+        if let Err(e) = shape.update(tessellator) {
+            log::warn!("{}", e);
+        }
 
         let drawable = Drawable::Polygon(shape);
 
-        let mut collider = CircleCollider::default();
-        collider.radius = radius;
+        let collider = CircleCollider { radius };
 
         let angle = offset_angle * i as f32 * (PI / 180.0);
-        let mut physics = Physics::default();
-        physics.velocity = Vec2::new(speed.x.abs() * angle.cos(), speed.y.abs() * angle.sin());
+        let physics = Physics {
+            velocity: Vec2::new(speed.x.abs() * angle.cos(), speed.y.abs() * angle.sin()),
+        };
 
         let lifespan = Lifespan {
             total: lifespan,
@@ -758,8 +801,12 @@ fn build_small_enemies(
 
         let score = Score { score };
 
+        let tweens = Tweens(vec![spin(), fade_out(lifespan.total)]);
+
         let mut eb = EntityBuilder::new();
-        eb.add_bundle((tag, transform, drawable, collider, physics, lifespan, score));
+        eb.add_bundle((
+            tag, transform, drawable, collider, physics, lifespan, score, tweens,
+        ));
         ebs.push(eb);
     }
 }
@@ -784,27 +831,36 @@ fn build_bullet(
     let mut shape = PolygonShape::default();
     shape.radius = bullet_config.shape_radius as f32;
     shape.point_count = bullet_config.vertices;
-    shape.fill_color = bullet_config.fill_color;
+    shape.fill = bullet_config.fill_color.into();
     shape.outline_color = bullet_config.outline_color;
     shape.outline_thickness = bullet_config.outline_thicknes as f32;
-    shape.update(tessellator);
+    shape.blend_mode = BlendMode::Additive;
+    if let Err(e) = shape.update(tessellator) {
+        log::warn!("{}", e);
+    }
 
     let drawable = Drawable::Polygon(shape);
 
-    let mut collider = CircleCollider::default();
-    collider.radius = bullet_config.collision_radius as f32;
+    let collider = CircleCollider {
+        radius: bullet_config.collision_radius as f32,
+    };
 
     let bullet_direction = (to - from).normalize_or_zero();
     let bullet_speed = Vec2::new(bullet_config.speed, bullet_config.speed) * bullet_direction;
-    let mut physics = Physics::default();
-    physics.velocity = bullet_speed;
+    let physics = Physics {
+        velocity: bullet_speed,
+    };
 
     let lifespan = Lifespan {
         total: bullet_config.lifespan,
         remaining: bullet_config.lifespan,
     };
 
-    eb.add_bundle((tag, transform, drawable, collider, physics, lifespan));
+    let tweens = Tweens(vec![spin(), fade_out(lifespan.total)]);
+
+    eb.add_bundle((
+        tag, transform, drawable, collider, physics, lifespan, tweens,
+    ));
 }
 
 fn build_special_weapon(
@@ -816,7 +872,7 @@ fn build_special_weapon(
     let position = parent_position;
     let speed = 2500.0;
     let radius = parent_shape.radius / 2.0;
-    let fill_color = parent_shape.fill_color;
+    let fill_color = enemy_color(&parent_shape.fill);
     let outline_color = parent_shape.outline_color;
     let outline_thickness = parent_shape.outline_thickness;
     let entity_count = 18;
@@ -834,27 +890,95 @@ fn build_special_weapon(
         let mut shape = PolygonShape::default();
         shape.radius = radius;
         shape.point_count = entity_count;
-        shape.fill_color = fill_color;
+        shape.fill = enemy_fill(fill_color, radius);
         shape.outline_color = outline_color;
         shape.outline_thickness = outline_thickness;
-        shape.update(tessellator);
+        shape.blend_mode = BlendMode::Additive;
+        if let Err(e) = shape.update(tessellator) {
+            log::warn!("{}", e);
+        }
 
         let drawable = Drawable::Polygon(shape);
 
-        let mut collider = CircleCollider::default();
-        collider.radius = radius;
+        let collider = CircleCollider { radius };
 
         let angle = offset_angle * i as f32 * (PI / 180.0);
-        let mut physics = Physics::default();
-        physics.velocity = Vec2::new(speed * angle.cos(), speed * angle.sin());
+        let physics = Physics {
+            velocity: Vec2::new(speed * angle.cos(), speed * angle.sin()),
+        };
 
         let lifespan = Lifespan {
             total: lifespan,
             remaining: lifespan,
         };
 
+        let tweens = Tweens(vec![spin(), fade_out(lifespan.total)]);
+
         let mut eb = EntityBuilder::new();
-        eb.add_bundle((tag, transform, drawable, collider, physics, lifespan));
+        eb.add_bundle((
+            tag, transform, drawable, collider, physics, lifespan, tweens,
+        ));
         ebs.push(eb);
     }
 }
+
+/// A burst of sparks in the color of whatever exploded.
+fn build_explosion(position: Vec2, color: Color) -> (Transform, ParticleEmitter) {
+    let mut transparent = color;
+    transparent.a = 0.0;
+
+    let mut emitter = ParticleEmitter::default();
+    emitter.emitting = false;
+    emitter.speed = 100.0..500.0;
+    emitter.lifetime = Duration::from_secs_f32(0.3)..Duration::from_secs_f32(1.0);
+    emitter.size_over_life = vec![(0.0, 6.0), (1.0, 1.0)];
+    emitter.color_over_life = vec![
+        (0.0, color.lerp(Color::WHITE, 0.6)),
+        (0.3, color),
+        (1.0, transparent),
+    ];
+    emitter.blend_mode = BlendMode::Additive;
+    emitter.burst(300);
+
+    (Transform::from_position(position.x, position.y), emitter)
+}
+
+/// Every shape keeps turning at 60 degrees per second.
+fn spin() -> Tween {
+    let mut tween = Tween::new(
+        TweenProperty::Rotation {
+            from: 0.0,
+            to: 360.0,
+        },
+        Duration::from_secs(6),
+        Easing::Linear,
+    );
+    tween.repeat = Repeat::Forever;
+    tween
+}
+
+/// Short-lived shapes fade out over their lifespan.
+fn fade_out(lifespan: Duration) -> Tween {
+    Tween::new(
+        TweenProperty::Alpha { from: 1.0, to: 0.0 },
+        lifespan,
+        Easing::Linear,
+    )
+}
+
+/// Enemies glow towards their center.
+fn enemy_fill(color: Color, radius: f32) -> Fill {
+    Fill::RadialGradient {
+        center: Vec2::new(radius, radius),
+        radius,
+        inner_color: color.lerp(Color::WHITE, 0.6),
+        outer_color: color,
+    }
+}
+
+fn enemy_color(fill: &Fill) -> Color {
+    match fill {
+        Fill::RadialGradient { outer_color, .. } => *outer_color,
+        fill => fill.color_at(Vec2::ZERO),
+    }
+}
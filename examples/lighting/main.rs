@@ -0,0 +1,103 @@
+use glam::Vec2;
+use hecs::World;
+use papercut::{
+    camera::Camera,
+    components::{Drawable, Transform},
+    graphics::{Color, Geometry, PolygonShape, RectangleShape, Tessellator},
+    input::{InputHelper, KeyCode},
+    lighting::{Light, LightKind, Shadows},
+    Context, RendererConfig, Scene, WindowConfig,
+};
+
+fn main() -> Result<(), papercut::PapercutError> {
+    let wc = WindowConfig::default();
+    let rc = RendererConfig {
+        clear_color: Color::new(0.6, 0.6, 0.6, 1.0),
+        ..Default::default()
+    };
+
+    papercut::init_logger();
+    papercut::start::<LightingExample>(wc, rc)
+}
+
+#[derive(Default)]
+struct LightingExample {
+    world: World,
+    mouse_light: Transform,
+}
+
+impl papercut::Game for LightingExample {
+    fn on_create(&mut self) {
+        let mut world = World::new();
+        let mut tessellator = Tessellator::new(0.02);
+
+        for (x, y) in [(250.0, 200.0), (600.0, 350.0), (800.0, 150.0)] {
+            let transform = Transform::from_position(x, y);
+            let mut rect = RectangleShape::default();
+            rect.size = Vec2::new(80.0, 40.0);
+            rect.fill = Color::new(0.8, 0.2, 0.2, 1.0).into();
+            rect.update(&mut tessellator).expect("rect should be valid");
+            world.spawn((transform, Drawable::Rect(rect)));
+        }
+
+        let mut transform = Transform::from_position(400.0, 400.0);
+        transform.origin = Vec2::new(40.0, 40.0);
+        let mut polygon = PolygonShape::default();
+        polygon.radius = 40.0;
+        polygon.point_count = 6;
+        polygon.fill = Color::new(0.2, 0.4, 0.8, 1.0).into();
+        polygon
+            .update(&mut tessellator)
+            .expect("polygon should be valid");
+        world.spawn((transform, Drawable::Polygon(polygon)));
+
+        let light = Light {
+            kind: LightKind::Spot {
+                direction: Vec2::new(1.0, -0.5),
+                inner_angle: 20.0,
+                outer_angle: 35.0,
+            },
+            color: Color::new(1.0, 0.9, 0.6, 1.0),
+            radius: 700.0,
+            shadows: Shadows::Hard,
+            ..Default::default()
+        };
+        world.spawn((Transform::from_position(50.0, 500.0), light));
+
+        self.world = world;
+    }
+
+    fn on_update(
+        &mut self,
+        input: &InputHelper,
+        _ctx: &mut Context,
+        camera: &Camera,
+        _dt: std::time::Duration,
+    ) -> bool {
+        self.mouse_light.translation = input.mouse_in_world(camera);
+
+        !input.quit() && !input.key_pressed(KeyCode::Escape)
+    }
+
+    fn on_render(&self, scene: &mut Scene, ctx: &mut Context) {
+        scene.set_ambient_light(Color::new(0.1, 0.1, 0.15, 1.0));
+
+        for (_id, (transform, drawable)) in self.world.query::<(&Transform, &Drawable)>().iter() {
+            ctx.draw_shape(transform, drawable, scene);
+            ctx.draw_occluder(transform, drawable, scene);
+        }
+
+        for (_id, (transform, light)) in self.world.query::<(&Transform, &Light)>().iter() {
+            ctx.draw_light(transform, light, scene);
+        }
+
+        let mouse_light = Light {
+            radius: 300.0,
+            shadows: Shadows::Soft {
+                source_radius: 10.0,
+            },
+            ..Default::default()
+        };
+        ctx.draw_light(&self.mouse_light, &mouse_light, scene);
+    }
+}
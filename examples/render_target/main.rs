@@ -0,0 +1,136 @@
+use glam::Vec2;
+use hecs::World;
+use papercut::{
+    camera::Camera,
+    components::{Drawable, Transform},
+    graphics::{CircleShape, Color, Geometry, RectangleShape, SpriteShape, Tessellator},
+    input::{InputHelper, KeyCode},
+    texture::RenderTarget,
+    Context, RendererConfig, Scene, WindowConfig,
+};
+
+const MINIMAP_WIDTH: u32 = 256;
+const MINIMAP_HEIGHT: u32 = 144;
+
+fn main() -> Result<(), papercut::PapercutError> {
+    let wc = WindowConfig::default();
+    let rc = RendererConfig {
+        clear_color: Color::new(0.1, 0.1, 0.1, 1.0),
+        ..Default::default()
+    };
+
+    papercut::init_logger();
+    papercut::start::<RenderTargetExample>(wc, rc)
+}
+
+struct RenderTargetExample {
+    world: World,
+    running_time: f32,
+    minimap: RenderTarget,
+    minimap_camera: Camera,
+    minimap_sprite: (Transform, Drawable),
+    minimap_border: (Transform, Drawable),
+}
+
+impl Default for RenderTargetExample {
+    fn default() -> Self {
+        let minimap = RenderTarget::new(MINIMAP_WIDTH, MINIMAP_HEIGHT);
+
+        Self {
+            world: World::new(),
+            running_time: 0.0,
+            minimap_camera: Camera::new(
+                papercut::DEFAULT_WINDOW_WIDTH,
+                papercut::DEFAULT_WINDOW_HEIGHT,
+            ),
+            minimap_sprite: (
+                Transform::default(),
+                Drawable::Sprite(SpriteShape::default()),
+            ),
+            minimap_border: (
+                Transform::default(),
+                Drawable::Rect(RectangleShape::default()),
+            ),
+            minimap,
+        }
+    }
+}
+
+impl papercut::Game for RenderTargetExample {
+    fn on_create(&mut self) {
+        let mut world = World::new();
+        let mut tessellator = Tessellator::new(0.02);
+
+        for i in 0..12 {
+            let x = 80.0 + (i % 6) as f32 * 150.0;
+            let y = 150.0 + (i / 6) as f32 * 250.0;
+            let mut circle = CircleShape::default();
+            circle.radius = 30.0 + (i % 3) as f32 * 10.0;
+            circle.fill = Color::new(0.2 + 0.06 * i as f32, 0.5, 0.9 - 0.06 * i as f32, 1.0).into();
+            circle
+                .update(&mut tessellator)
+                .expect("circle should be valid");
+            world.spawn((Transform::from_position(x, y), Drawable::Circle(circle)));
+        }
+
+        let size = Vec2::new(MINIMAP_WIDTH as f32, MINIMAP_HEIGHT as f32);
+        let position = Vec2::new(16.0, 16.0);
+
+        let mut sprite = SpriteShape::default();
+        sprite.size = size;
+        sprite.texture = Some(self.minimap.texture().clone());
+        sprite
+            .update(&mut tessellator)
+            .expect("sprite should be valid");
+        let mut transform = Transform::from_position(position.x, position.y);
+        transform.z_index = 10.0;
+        self.minimap_sprite = (transform, Drawable::Sprite(sprite));
+
+        let mut border = RectangleShape::default();
+        border.size = size + 8.0;
+        border.fill = Color::WHITE.into();
+        border
+            .update(&mut tessellator)
+            .expect("border should be valid");
+        let mut transform = Transform::from_position(position.x - 4.0, position.y - 4.0);
+        transform.z_index = 9.0;
+        self.minimap_border = (transform, Drawable::Rect(border));
+
+        self.world = world;
+    }
+
+    fn on_update(
+        &mut self,
+        input: &InputHelper,
+        _ctx: &mut Context,
+        _camera: &Camera,
+        dt: std::time::Duration,
+    ) -> bool {
+        self.running_time += dt.as_secs_f32();
+        for (_id, transform) in self.world.query_mut::<&mut Transform>() {
+            transform.translation.y += (self.running_time * 2.0).sin() * 0.5;
+        }
+
+        !input.quit() && !input.key_pressed(KeyCode::Escape)
+    }
+
+    fn on_render(&self, scene: &mut Scene, ctx: &mut Context) {
+        let mut minimap_scene = Scene::default();
+        for (_id, (transform, drawable)) in self.world.query::<(&Transform, &Drawable)>().iter() {
+            ctx.draw_shape(transform, drawable, scene);
+            ctx.draw_shape(transform, drawable, &mut minimap_scene);
+        }
+
+        scene.render_to(
+            &self.minimap,
+            &self.minimap_camera,
+            Color::new(0.0, 0.0, 0.2, 1.0),
+            minimap_scene,
+        );
+
+        let (transform, drawable) = &self.minimap_sprite;
+        ctx.draw_shape(transform, drawable, scene);
+        let (transform, drawable) = &self.minimap_border;
+        ctx.draw_shape(transform, drawable, scene);
+    }
+}
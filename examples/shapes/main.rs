@@ -0,0 +1,187 @@
+use glam::Vec2;
+use papercut::{
+    camera::Camera,
+    components::{Drawable, Transform},
+    graphics::{
+        ArcShape, BlendMode, Color, EllipseShape, Geometry, LineCap, LineJoin, PolylineShape,
+        RingShape, RoundedRectangleShape, SectorShape, Tessellator,
+    },
+    input::{InputHelper, KeyCode},
+    Context, RendererConfig, Scene, WindowConfig,
+};
+
+const COOLDOWN: f32 = 3.0;
+const PING_INTERVAL: f32 = 2.0;
+const PING_RADIUS: f32 = 150.0;
+const TRAIL_LENGTH: usize = 40;
+
+fn main() -> Result<(), papercut::PapercutError> {
+    let wc = WindowConfig::default();
+    let rc = RendererConfig {
+        clear_color: Color::new(0.05, 0.08, 0.05, 1.0),
+        ..Default::default()
+    };
+
+    papercut::init_logger();
+    papercut::start::<ShapesExample>(wc, rc)
+}
+
+struct ShapesExample {
+    tessellator: Tessellator,
+    running_time: f32,
+    static_shapes: Vec<(Transform, Drawable)>,
+    cooldown: (Transform, Drawable),
+    cooldown_rim: (Transform, Drawable),
+    ping: (Transform, Drawable),
+    trail: (Transform, Drawable),
+}
+
+impl Default for ShapesExample {
+    fn default() -> Self {
+        Self {
+            tessellator: Tessellator::new(0.05),
+            running_time: 0.0,
+            static_shapes: Vec::new(),
+            cooldown: (
+                Transform::default(),
+                Drawable::Sector(SectorShape::default()),
+            ),
+            cooldown_rim: (Transform::default(), Drawable::Arc(ArcShape::default())),
+            ping: (Transform::default(), Drawable::Ring(RingShape::default())),
+            trail: (
+                Transform::default(),
+                Drawable::Polyline(PolylineShape::default()),
+            ),
+        }
+    }
+}
+
+impl papercut::Game for ShapesExample {
+    fn on_create(&mut self) {
+        // A UI panel holding the cooldown indicator.
+        let mut panel = RoundedRectangleShape::default();
+        panel.size = Vec2::new(160.0, 160.0);
+        panel.corner_radius = 20.0;
+        panel.fill = Color::new(0.1, 0.1, 0.15, 0.9).into();
+        panel.outline_thickness = 2.0;
+        panel.outline_color = Color::new(0.6, 0.6, 0.7, 1.0);
+        panel
+            .update(&mut self.tessellator)
+            .expect("panel should be valid");
+        self.static_shapes.push((
+            Transform::from_position(40.0, 40.0),
+            Drawable::RoundedRect(panel),
+        ));
+
+        let mut radar = EllipseShape::default();
+        radar.radii = Vec2::new(PING_RADIUS, PING_RADIUS * 0.6);
+        radar.fill = Color::new(0.0, 0.2, 0.0, 1.0).into();
+        radar.outline_thickness = 3.0;
+        radar.outline_color = Color::new(0.2, 0.8, 0.2, 1.0);
+        radar
+            .update(&mut self.tessellator)
+            .expect("radar should be valid");
+        let mut transform = Transform::from_position(600.0, 300.0);
+        transform.origin = radar.radii;
+        self.static_shapes
+            .push((transform, Drawable::Ellipse(radar)));
+
+        let mut sector = SectorShape::default();
+        sector.radius = 60.0;
+        sector.fill = Color::new(0.9, 0.6, 0.1, 1.0).into();
+        let mut transform = Transform::from_position(120.0, 120.0);
+        transform.origin = Vec2::splat(sector.radius);
+        transform.z_index = 1.0;
+        self.cooldown = (transform, Drawable::Sector(sector));
+
+        let mut rim = ArcShape::default();
+        rim.radius = 66.0;
+        rim.outline_thickness = 6.0;
+        rim.outline_color = Color::WHITE;
+        rim.outline_style.cap = LineCap::Round;
+        let mut transform = Transform::from_position(120.0, 120.0);
+        transform.origin = Vec2::splat(rim.radius);
+        transform.z_index = 2.0;
+        self.cooldown_rim = (transform, Drawable::Arc(rim));
+
+        let mut ring = RingShape::default();
+        ring.blend_mode = BlendMode::Additive;
+        // Squashed to match the radar.
+        let mut transform = Transform::from_position(600.0, 300.0);
+        transform.scale = Vec2::new(1.0, 0.6);
+        transform.z_index = 1.0;
+        self.ping = (transform, Drawable::Ring(ring));
+
+        let mut trail = PolylineShape::default();
+        trail.outline_style.join = LineJoin::Round;
+        trail.outline_style.cap = LineCap::Round;
+        trail.blend_mode = BlendMode::Additive;
+        let transform = Transform {
+            z_index: 3.0,
+            ..Default::default()
+        };
+        self.trail = (transform, Drawable::Polyline(trail));
+    }
+
+    fn on_update(
+        &mut self,
+        input: &InputHelper,
+        _ctx: &mut Context,
+        camera: &Camera,
+        dt: std::time::Duration,
+    ) -> bool {
+        self.running_time += dt.as_secs_f32();
+
+        let progress = (self.running_time % COOLDOWN) / COOLDOWN;
+        if let (_, Drawable::Sector(sector)) = &mut self.cooldown {
+            sector.sweep_angle = 360.0 * progress;
+            if let Err(e) = sector.update(&mut self.tessellator) {
+                log::warn!("{}", e);
+            }
+        }
+        if let (_, Drawable::Arc(rim)) = &mut self.cooldown_rim {
+            rim.sweep_angle = 360.0 * progress;
+            if let Err(e) = rim.update(&mut self.tessellator) {
+                log::warn!("{}", e);
+            }
+        }
+
+        let ping_progress = (self.running_time % PING_INTERVAL) / PING_INTERVAL;
+        if let (transform, Drawable::Ring(ring)) = &mut self.ping {
+            ring.radius = PING_RADIUS * ping_progress;
+            ring.inner_radius = (ring.radius - 8.0).max(0.0);
+            ring.fill = Color::new(0.2, 1.0, 0.2, 1.0 - ping_progress).into();
+            if let Err(e) = ring.update(&mut self.tessellator) {
+                log::warn!("{}", e);
+            }
+            transform.origin = Vec2::splat(ring.radius) * transform.scale;
+        }
+
+        // A trail behind the mouse that thins and fades towards its end.
+        if let (_, Drawable::Polyline(trail)) = &mut self.trail {
+            trail.points.insert(0, input.mouse_in_world(camera));
+            trail.points.truncate(TRAIL_LENGTH);
+            let count = trail.points.len();
+            let fade = |i: usize| 1.0 - i as f32 / TRAIL_LENGTH as f32;
+            trail.thicknesses = (0..count).map(|i| 12.0 * fade(i)).collect();
+            trail.colors = (0..count)
+                .map(|i| Color::new(0.3, 0.6, 1.0, fade(i)))
+                .collect();
+            if let Err(e) = trail.update(&mut self.tessellator) {
+                log::warn!("{}", e);
+            }
+        }
+
+        !input.quit() && !input.key_pressed(KeyCode::Escape)
+    }
+
+    fn on_render(&self, scene: &mut Scene, ctx: &mut Context) {
+        for (transform, drawable) in &self.static_shapes {
+            ctx.draw_shape(transform, drawable, scene);
+        }
+
+        for (transform, drawable) in [&self.cooldown, &self.cooldown_rim, &self.ping, &self.trail] {
+            ctx.draw_shape(transform, drawable, scene);
+        }
+    }
+}
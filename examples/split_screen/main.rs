@@ -0,0 +1,152 @@
+use glam::Vec2;
+use hecs::World;
+use papercut::{
+    camera::Camera,
+    components::{Drawable, Transform},
+    graphics::{CircleShape, Color, Geometry, Rect, RectangleShape, Tessellator},
+    input::{InputHelper, KeyCode},
+    Context, RendererConfig, Scene, WindowConfig,
+};
+
+const PLAYER_SPEED: f32 = 4.0;
+const ZOOM_SPEED: f32 = 1.02;
+
+fn main() -> Result<(), papercut::PapercutError> {
+    let wc = WindowConfig::default();
+    let rc = RendererConfig {
+        clear_color: Color::new(0.15, 0.15, 0.2, 1.0),
+        ..Default::default()
+    };
+
+    papercut::init_logger();
+    papercut::start::<SplitScreenExample>(wc, rc)
+}
+
+struct Player {
+    camera: usize,
+    up: KeyCode,
+    left: KeyCode,
+    down: KeyCode,
+    right: KeyCode,
+}
+
+#[derive(Default)]
+struct SplitScreenExample {
+    world: World,
+    cameras_created: bool,
+}
+
+impl papercut::Game for SplitScreenExample {
+    fn on_create(&mut self) {
+        let mut world = World::new();
+        let mut tessellator = Tessellator::new(0.02);
+
+        for x in 0..10 {
+            for y in 0..10 {
+                let mut rect = RectangleShape::default();
+                rect.size = Vec2::new(40.0, 40.0);
+                rect.fill = Color::new(0.1 * x as f32, 0.1 * y as f32, 0.5, 1.0).into();
+                rect.update(&mut tessellator).expect("rect should be valid");
+                let transform = Transform::from_position(x as f32 * 150.0, y as f32 * 150.0);
+                world.spawn((transform, Drawable::Rect(rect)));
+            }
+        }
+
+        let players = [
+            (
+                Color::new(0.9, 0.3, 0.3, 1.0),
+                [KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D],
+            ),
+            (
+                Color::new(0.3, 0.9, 0.3, 1.0),
+                [KeyCode::Up, KeyCode::Left, KeyCode::Down, KeyCode::Right],
+            ),
+        ];
+        for (camera, (color, [up, left, down, right])) in players.into_iter().enumerate() {
+            let mut circle = CircleShape::default();
+            circle.radius = 20.0;
+            circle.fill = color.into();
+            circle
+                .update(&mut tessellator)
+                .expect("circle should be valid");
+
+            let mut transform = Transform::from_position(300.0 + camera as f32 * 200.0, 300.0);
+            transform.origin = Vec2::new(20.0, 20.0);
+            transform.z_index = 1.0;
+
+            let player = Player {
+                camera,
+                up,
+                left,
+                down,
+                right,
+            };
+            world.spawn((transform, Drawable::Circle(circle), player));
+        }
+
+        self.world = world;
+    }
+
+    fn on_update(
+        &mut self,
+        input: &InputHelper,
+        ctx: &mut Context,
+        _camera: &Camera,
+        _dt: std::time::Duration,
+    ) -> bool {
+        if !self.cameras_created {
+            let size = ctx.window_size();
+            let mut left = Camera::new(size.x, size.y);
+            left.set_viewport(Rect::new(Vec2::ZERO, Vec2::new(0.5, 1.0)));
+            let mut right = Camera::new(size.x, size.y);
+            right.set_viewport(Rect::new(Vec2::new(0.5, 0.0), Vec2::new(0.5, 1.0)));
+            ctx.set_cameras(vec![left, right]);
+            self.cameras_created = true;
+        }
+
+        // Shapes are tessellated again for their zoomed size, so circles stay round up close.
+        for camera in ctx.cameras_mut() {
+            if input.key_held(KeyCode::PageUp) {
+                camera.set_zoom((camera.zoom() * ZOOM_SPEED).min(16.0));
+            }
+            if input.key_held(KeyCode::PageDown) {
+                camera.set_zoom((camera.zoom() / ZOOM_SPEED).max(0.25));
+            }
+        }
+
+        for (_id, (transform, player)) in self.world.query_mut::<(&mut Transform, &Player)>() {
+            let mut direction = Vec2::ZERO;
+            if input.key_held(player.up) {
+                direction.y += 1.0;
+            }
+            if input.key_held(player.down) {
+                direction.y -= 1.0;
+            }
+            if input.key_held(player.left) {
+                direction.x -= 1.0;
+            }
+            if input.key_held(player.right) {
+                direction.x += 1.0;
+            }
+            transform.translation += direction.normalize_or_zero() * PLAYER_SPEED;
+
+            let camera = &mut ctx.cameras_mut()[player.camera];
+            let half_size = camera.world_size() / 2.0;
+            camera.set_position(transform.translation - half_size);
+        }
+
+        if input.mouse_pressed(papercut::input::MouseButton::Left) {
+            if let Some((camera, position)) = input.mouse_in_viewports(ctx.cameras()) {
+                ctx.set_window_title(format!("Clicked {} in viewport {}", position, camera));
+            }
+        }
+
+        !input.quit() && !input.key_pressed(KeyCode::Escape)
+    }
+
+    fn on_render(&self, scene: &mut Scene, ctx: &mut Context) {
+        for (_id, (transform, drawable)) in self.world.query::<(&Transform, &Drawable)>().iter() {
+            ctx.draw_shape(transform, drawable, scene);
+        }
+    }
+}
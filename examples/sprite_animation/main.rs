@@ -0,0 +1,118 @@
+use hecs::World;
+use papercut::{
+    animation::{load_aseprite, update_animators, Animator},
+    camera::Camera,
+    components::{Drawable, Transform},
+    graphics::{Color, Geometry, SpriteShape, Tessellator},
+    input::{InputHelper, KeyCode},
+    Context, RendererConfig, Scene, WindowConfig,
+};
+
+const SPRITE_SHEET: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/sprite_animation/chomp.json"
+);
+const CHOMPERS: usize = 5;
+const SPEED_STEP: f32 = 1.25;
+
+fn main() -> Result<(), papercut::PapercutError> {
+    let wc = WindowConfig::default();
+    let rc = RendererConfig {
+        clear_color: Color::new(0.05, 0.05, 0.15, 1.0),
+        ..Default::default()
+    };
+
+    papercut::init_logger();
+    papercut::start::<SpriteAnimationExample>(wc, rc)
+}
+
+/// Moves an entity to the right, wrapping around the window.
+struct Walk {
+    speed: f32,
+}
+
+#[derive(Default)]
+struct SpriteAnimationExample {
+    world: World,
+}
+
+impl papercut::Game for SpriteAnimationExample {
+    fn on_create(&mut self) {
+        let mut tessellator = Tessellator::new(0.02);
+        let sheet = load_aseprite(SPRITE_SHEET).expect("chomp.json should be a sprite sheet");
+        let source = sheet.clips["chomp"].frames[0].source;
+
+        // Bigger chompers chomp slower and walk faster.
+        for i in 0..CHOMPERS {
+            let scale = 1.0 + i as f32 * 0.5;
+            let mut sprite = SpriteShape::default();
+            sprite.size = source.size * scale;
+            sprite.texture = Some(sheet.texture.clone());
+            sprite.source = Some(source);
+            sprite
+                .update(&mut tessellator)
+                .expect("sprite should be valid");
+
+            let mut animator = Animator::new(sheet.clips.clone());
+            animator.speed = 1.0 / scale;
+            animator.play("chomp");
+
+            let y = 40.0 + i as f32 * 110.0;
+            let walk = Walk {
+                speed: 60.0 * scale,
+            };
+            self.world.spawn((
+                Transform::from_position(i as f32 * 150.0, y),
+                Drawable::Sprite(sprite),
+                animator,
+                walk,
+            ));
+        }
+    }
+
+    fn on_update(
+        &mut self,
+        input: &InputHelper,
+        ctx: &mut Context,
+        _camera: &Camera,
+        dt: std::time::Duration,
+    ) -> bool {
+        let pause = input.key_pressed(KeyCode::Space);
+        let speed = if input.key_pressed(KeyCode::Up) {
+            SPEED_STEP
+        } else if input.key_pressed(KeyCode::Down) {
+            1.0 / SPEED_STEP
+        } else {
+            1.0
+        };
+
+        for (_id, animator) in self.world.query_mut::<&mut Animator>() {
+            animator.paused ^= pause;
+            animator.speed *= speed;
+        }
+        update_animators(&mut self.world, dt);
+
+        let width = ctx.window_size().x;
+        for (_id, (transform, walk, animator)) in
+            self.world.query_mut::<(&mut Transform, &Walk, &Animator)>()
+        {
+            if animator.paused {
+                continue;
+            }
+            transform.translation.x += walk.speed * dt.as_secs_f32();
+            if transform.translation.x > width {
+                // Far enough left for the biggest chomper to walk in from off screen.
+                transform.translation.x -= width + 200.0;
+            }
+        }
+
+        !input.quit() && !input.key_pressed(KeyCode::Escape)
+    }
+
+    fn on_render(&self, scene: &mut Scene, ctx: &mut Context) {
+        let mut query = self.world.query::<(&Transform, &Drawable, &Animator)>();
+        for (_id, (transform, drawable, animator)) in query.iter() {
+            ctx.draw_animated(transform, drawable, animator, scene);
+        }
+    }
+}
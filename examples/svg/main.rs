@@ -0,0 +1,66 @@
+use glam::Vec2;
+use papercut::{
+    camera::Camera,
+    components::{Drawable, Transform},
+    graphics::{Color, Tessellator},
+    input::{InputHelper, KeyCode},
+    Context, RendererConfig, Scene, WindowConfig,
+};
+
+const SHIP_SIZE: f32 = 200.0;
+
+fn main() -> Result<(), papercut::PapercutError> {
+    let wc = WindowConfig::default();
+    let rc = RendererConfig {
+        clear_color: Color::new(0.02, 0.02, 0.05, 1.0),
+        ..Default::default()
+    };
+
+    papercut::init_logger();
+    papercut::start::<SvgExample>(wc, rc)
+}
+
+#[derive(Default)]
+struct SvgExample {
+    ship: Vec<Drawable>,
+    transforms: Vec<Transform>,
+    running_time: f32,
+}
+
+impl papercut::Game for SvgExample {
+    fn on_create(&mut self) {
+        let mut tessellator = Tessellator::new(0.05);
+        self.ship = papercut::svg::parse(include_bytes!("ship.svg"), &mut tessellator)
+            .expect("ship.svg should be valid");
+
+        for i in 0..3 {
+            let mut transform = Transform::from_position(100.0 + i as f32 * 300.0, 180.0);
+            transform.origin = Vec2::splat(SHIP_SIZE / 2.0);
+            transform.scale = Vec2::splat(0.75 + 0.25 * i as f32);
+            self.transforms.push(transform);
+        }
+    }
+
+    fn on_update(
+        &mut self,
+        input: &InputHelper,
+        _ctx: &mut Context,
+        _camera: &Camera,
+        dt: std::time::Duration,
+    ) -> bool {
+        self.running_time += dt.as_secs_f32();
+        for (i, transform) in self.transforms.iter_mut().enumerate() {
+            transform.rotation = (self.running_time + i as f32).sin() * 20.0;
+        }
+
+        !input.quit() && !input.key_pressed(KeyCode::Escape)
+    }
+
+    fn on_render(&self, scene: &mut Scene, ctx: &mut Context) {
+        for transform in &self.transforms {
+            for drawable in &self.ship {
+                ctx.draw_shape(transform, drawable, scene);
+            }
+        }
+    }
+}
@@ -9,6 +9,7 @@ struct Globals {
 
 struct VertexOutput {
     @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
     @builtin(position) position: vec4<f32>,
 };
 
@@ -16,20 +17,24 @@ struct VertexOutput {
 fn vs_main(
     @location(0) position: vec4<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>,
 ) -> VertexOutput {
     var clip_position = globals.projection * globals.view * position;
     
-    return VertexOutput(color, clip_position);
+    return VertexOutput(color, uv, clip_position);
 }
 
 
 // Fragment
 
+@group(2) @binding(0) var texture: texture_2d<f32>;
+@group(2) @binding(1) var texture_sampler: sampler;
+
 struct Output {
     @location(0) color: vec4<f32>,
 };
 
 @fragment
-fn fs_main(@location(0) color: vec4<f32>) -> Output {
-    return Output(color);
+fn fs_main(@location(0) color: vec4<f32>, @location(1) uv: vec2<f32>) -> Output {
+    return Output(color * textureSample(texture, texture_sampler, uv));
 }
@@ -0,0 +1,22 @@
+// Multiplies the scene by the light accumulation buffer.
+
+@group(0) @binding(0) var light_buffer: texture_2d<f32>;
+@group(0) @binding(1) var light_sampler: sampler;
+
+struct CompositeOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_composite(@builtin(vertex_index) index: u32) -> CompositeOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    let position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return CompositeOutput(position, uv);
+}
+
+@fragment
+fn fs_composite(in: CompositeOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(textureSample(light_buffer, light_sampler, in.uv).rgb, 1.0);
+}
@@ -0,0 +1,61 @@
+struct Globals {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+
+// Light
+
+struct LightOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec2<f32>,
+    @location(1) center: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    // x: radius, y: falloff, z: cosine of the outer cone angle, w: cosine of the inner cone angle.
+    @location(3) params: vec4<f32>,
+    @location(4) direction: vec2<f32>,
+};
+
+@vertex
+fn vs_light(
+    @location(0) position: vec2<f32>,
+    @location(1) center: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) params: vec4<f32>,
+    @location(4) direction: vec2<f32>,
+) -> LightOutput {
+    let clip_position = globals.projection * globals.view * vec4<f32>(position, 0.0, 1.0);
+
+    return LightOutput(clip_position, position, center, color, params, direction);
+}
+
+@fragment
+fn fs_light(in: LightOutput) -> @location(0) vec4<f32> {
+    let to_fragment = in.world_position - in.center;
+    let distance = length(to_fragment);
+    var attenuation = pow(clamp(1.0 - distance / in.params.x, 0.0, 1.0), in.params.y);
+
+    // Point lights have no direction.
+    if (length(in.direction) > 0.0 && distance > 0.0) {
+        let cos_angle = dot(to_fragment / distance, in.direction);
+        attenuation = attenuation * smoothstep(in.params.z, in.params.w, cos_angle);
+    }
+
+    return vec4<f32>(in.color.rgb * attenuation, 1.0);
+}
+
+
+// Shadow
+
+@vertex
+fn vs_shadow(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return globals.projection * globals.view * vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_shadow() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0);
+}
+
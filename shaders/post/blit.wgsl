@@ -0,0 +1,6 @@
+// Copies the source to the target unchanged.
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(source, source_sampler, in.uv);
+}
@@ -0,0 +1,28 @@
+// params[0].x: brightness threshold, params[0].y: intensity, params[0].z: radius in pixels.
+
+fn bright_pass(uv: vec2<f32>) -> vec3<f32> {
+    let color = textureSample(source, source_sampler, uv).rgb;
+    let brightness = max(color.r, max(color.g, color.b));
+    return color * max(brightness - post.params[0].x, 0.0) / max(brightness, 0.0001);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(source, source_sampler, in.uv).rgb;
+    let radius = post.params[0].z;
+
+    // Samples a disc along a golden angle spiral, weighting samples closer to the centre higher.
+    let sample_count = 32;
+    var glow = vec3<f32>(0.0);
+    var total_weight = 0.0;
+    for (var i = 0; i < sample_count; i = i + 1) {
+        let t = (f32(i) + 0.5) / f32(sample_count);
+        let angle = f32(i) * 2.39996323;
+        let offset = vec2<f32>(cos(angle), sin(angle)) * sqrt(t) * radius * post.resolution.zw;
+        let weight = 1.0 - t;
+        glow = glow + bright_pass(in.uv + offset) * weight;
+        total_weight = total_weight + weight;
+    }
+
+    return vec4<f32>(color + glow / total_weight * post.params[0].y, 1.0);
+}
@@ -0,0 +1,11 @@
+// params[0].x: offset in pixels at the edges of the target.
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = (in.uv - 0.5) * 2.0 * post.params[0].x * post.resolution.zw;
+    let r = textureSample(source, source_sampler, in.uv + offset).r;
+    let g = textureSample(source, source_sampler, in.uv).g;
+    let b = textureSample(source, source_sampler, in.uv - offset).b;
+
+    return vec4<f32>(r, g, b, 1.0);
+}
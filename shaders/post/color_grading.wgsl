@@ -0,0 +1,15 @@
+// params[0].x: exposure in stops, params[0].y: contrast, params[0].z: saturation,
+// params[1]: tint.
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color = textureSample(source, source_sampler, in.uv).rgb;
+
+    color = color * exp2(post.params[0].x);
+    color = (color - 0.5) * post.params[0].y + 0.5;
+    let luma = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    color = mix(vec3<f32>(luma), color, post.params[0].z);
+    color = color * post.params[1].rgb;
+
+    return vec4<f32>(max(color, vec3<f32>(0.0)), 1.0);
+}
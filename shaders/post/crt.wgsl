@@ -0,0 +1,18 @@
+// params[0].x: scanline intensity, params[0].y: scanline count, params[0].z: curvature.
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let centered = in.uv * 2.0 - 1.0;
+    let bulge = 1.0 + dot(centered, centered) * post.params[0].z;
+    let uv = centered * bulge * 0.5 + 0.5;
+    let color = textureSample(source, source_sampler, uv).rgb;
+
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    let scanline = 0.5 + 0.5 * sin(uv.y * post.params[0].y * 3.14159265);
+    let darkening = 1.0 - post.params[0].x * (1.0 - scanline);
+
+    return vec4<f32>(color * darkening, 1.0);
+}
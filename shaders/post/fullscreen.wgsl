@@ -0,0 +1,26 @@
+// Shared by every post-processing effect; the effect's fragment shader is appended.
+
+struct PostParams {
+    // xy: target size in pixels, zw: size of one pixel in uv units.
+    resolution: vec4<f32>,
+    params: array<vec4<f32>, 2>,
+};
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var<uniform> post: PostParams;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// A single triangle covering the whole target.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    let position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return VertexOutput(position, uv);
+}
+
@@ -0,0 +1,11 @@
+// params[0].x: intensity, params[0].y: smoothness.
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(source, source_sampler, in.uv).rgb;
+    // 0 at the centre, 1 in the corners.
+    let distance = length(in.uv - 0.5) * 1.41421356;
+    let vignette = 1.0 - post.params[0].x * smoothstep(1.0 - post.params[0].y, 1.0, distance);
+
+    return vec4<f32>(color * vignette, 1.0);
+}
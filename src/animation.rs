@@ -0,0 +1,513 @@
+//! Frame-based sprite animation.
+//!
+//! An [`AnimationClip`] is a sequence of regions of a sprite's texture, defined from a grid with
+//! [`AnimationClip::from_grid`] or loaded from a sprite sheet exported by Aseprite as JSON with
+//! [`load_aseprite`], where each tag becomes a clip and the sheet's image becomes the texture to
+//! draw the sprite with. An [`Animator`] component plays the clips:
+//! [`update_animators`] advances it in [`crate::Game::on_update`] and
+//! [`crate::Context::draw_animated`] draws the sprite with its current frame.
+//!
+//! Aseprite sheets can be exported with frames as an array or as a hash; in the latter case the
+//! frames are taken in the order they appear in the file.
+
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use glam::Vec2;
+use hecs::{Entity, World};
+use serde_json::Value;
+
+use crate::{error::SpriteSheetError, graphics::Rect, texture::Texture};
+
+/// One image of a clip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Region of the texture to draw, in texels from the top left.
+    pub source: Rect,
+    pub duration: Duration,
+    /// Reported by [`update_animators`] when the frame starts showing, e.g. to play a footstep
+    /// sound on the frame a foot touches the ground.
+    pub event: Option<String>,
+}
+
+/// What a clip does after its last frame.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LoopMode {
+    /// Stops on the last frame.
+    Once,
+    /// Starts over from the first frame.
+    #[default]
+    Loop,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AnimationClip {
+    pub frames: Vec<Frame>,
+    pub loop_mode: LoopMode,
+}
+
+impl AnimationClip {
+    /// A clip of cells of a grid of `frame_size` texels with `columns` cells per row, starting at
+    /// the top left of the texture. Cells are numbered row by row and played in the order given
+    /// by `frames`, each for `frame_duration`.
+    pub fn from_grid(
+        frame_size: Vec2,
+        columns: u32,
+        frames: impl IntoIterator<Item = u32>,
+        frame_duration: Duration,
+    ) -> Self {
+        let columns = columns.max(1);
+        let frames = frames
+            .into_iter()
+            .map(|cell| {
+                let position = Vec2::new((cell % columns) as f32, (cell / columns) as f32);
+                Frame {
+                    source: Rect::new(position * frame_size, frame_size),
+                    duration: frame_duration,
+                    event: None,
+                }
+            })
+            .collect();
+
+        Self {
+            frames,
+            loop_mode: LoopMode::default(),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+}
+
+/// The image of a sprite sheet and the clips of the animations on it.
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    pub texture: Texture,
+    pub clips: HashMap<String, AnimationClip>,
+}
+
+/// Loads the Aseprite JSON sprite sheet at `path` along with its PNG image, which is looked up
+/// relative to the JSON file like Aseprite exports it. See [`parse_aseprite`] for the clips.
+pub fn load_aseprite(path: impl AsRef<Path>) -> Result<SpriteSheet, SpriteSheetError> {
+    let path = path.as_ref();
+    let json: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+    let clips = parse_clips(&json)?;
+
+    let image = json["meta"]["image"]
+        .as_str()
+        .ok_or_else(|| invalid("missing image"))?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let texture = Texture::from_png(directory.join(image))?;
+
+    Ok(SpriteSheet { texture, clips })
+}
+
+/// Reads the clips of an Aseprite JSON sprite sheet that is already in memory, one per tag
+/// named after it. Tags played in reverse get their frames reversed, ping-pong tags loop as
+/// [`LoopMode::PingPong`] and tags repeated once play once. Sheets without tags have a single
+/// looping clip named `"default"` with every frame. The sheet's image is not loaded, see
+/// [`load_aseprite`].
+pub fn parse_aseprite(data: &[u8]) -> Result<HashMap<String, AnimationClip>, SpriteSheetError> {
+    parse_clips(&serde_json::from_slice(data)?)
+}
+
+fn parse_clips(json: &Value) -> Result<HashMap<String, AnimationClip>, SpriteSheetError> {
+    let frames = match &json["frames"] {
+        Value::Array(frames) => frames.iter().collect::<Vec<_>>(),
+        Value::Object(frames) => frames.values().collect(),
+        _ => return Err(invalid("missing frames")),
+    };
+    let frames = frames
+        .into_iter()
+        .enumerate()
+        .map(|(i, frame)| parse_frame(frame).ok_or_else(|| invalid(format!("frame {}", i))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tags = match &json["meta"]["frameTags"] {
+        Value::Array(tags) if !tags.is_empty() => tags,
+        _ => {
+            let clip = AnimationClip {
+                frames,
+                loop_mode: LoopMode::Loop,
+            };
+            return Ok(HashMap::from([("default".to_string(), clip)]));
+        }
+    };
+
+    tags.iter()
+        .map(|tag| {
+            let name = tag["name"]
+                .as_str()
+                .ok_or_else(|| invalid("tag without name"))?;
+            let range = tag_range(tag, frames.len())
+                .ok_or_else(|| invalid(format!("frames of tag {:?}", name)))?;
+            let direction = tag["direction"].as_str().unwrap_or("forward");
+
+            let mut clip_frames = frames[range].to_vec();
+            if direction.ends_with("reverse") {
+                clip_frames.reverse();
+            }
+            let loop_mode = if direction.starts_with("pingpong") {
+                LoopMode::PingPong
+            } else if tag["repeat"].as_str() == Some("1") {
+                LoopMode::Once
+            } else {
+                LoopMode::Loop
+            };
+
+            let clip = AnimationClip {
+                frames: clip_frames,
+                loop_mode,
+            };
+            Ok((name.to_string(), clip))
+        })
+        .collect()
+}
+
+fn invalid(reason: impl Into<String>) -> SpriteSheetError {
+    SpriteSheetError::Invalid(reason.into())
+}
+
+fn parse_frame(frame: &Value) -> Option<Frame> {
+    let region = &frame["frame"];
+    let position = Vec2::new(region["x"].as_f64()? as f32, region["y"].as_f64()? as f32);
+    let size = Vec2::new(region["w"].as_f64()? as f32, region["h"].as_f64()? as f32);
+    // Aseprite exports durations in milliseconds.
+    let duration = Duration::from_millis(frame["duration"].as_u64().unwrap_or(100));
+
+    Some(Frame {
+        source: Rect::new(position, size),
+        duration,
+        event: None,
+    })
+}
+
+fn tag_range(tag: &Value, frame_count: usize) -> Option<std::ops::RangeInclusive<usize>> {
+    let from = tag["from"].as_u64()? as usize;
+    let to = tag["to"].as_u64()? as usize;
+    (from <= to && to < frame_count).then_some(from..=to)
+}
+
+/// Where the animator is in its current clip.
+#[derive(Debug, Clone)]
+struct Playback {
+    clip: String,
+    frame: usize,
+    /// How long the current frame has been showing.
+    frame_time: Duration,
+    /// Whether a ping-pong clip is playing backwards.
+    backwards: bool,
+    finished: bool,
+    /// Whether the current frame's event was reported.
+    reported: bool,
+}
+
+/// Plays one of an entity's clips on its sprite.
+#[derive(Debug, Clone)]
+pub struct Animator {
+    pub clips: HashMap<String, AnimationClip>,
+    /// How fast clips play, e.g. 2.0 for twice their frame rate.
+    pub speed: f32,
+    pub paused: bool,
+    playback: Option<Playback>,
+}
+
+impl Animator {
+    pub fn new(clips: HashMap<String, AnimationClip>) -> Self {
+        Self {
+            clips,
+            speed: 1.0,
+            paused: false,
+            playback: None,
+        }
+    }
+
+    /// Plays `clip` from its first frame, unless it is already playing. Unknown clips are
+    /// ignored with a warning.
+    pub fn play(&mut self, clip: &str) {
+        if self.clip() == Some(clip) && !self.is_finished() {
+            return;
+        }
+        if !self.clips.contains_key(clip) {
+            log::warn!("animator has no clip named {:?}", clip);
+            return;
+        }
+
+        self.playback = Some(Playback {
+            clip: clip.to_string(),
+            frame: 0,
+            frame_time: Duration::ZERO,
+            backwards: false,
+            finished: false,
+            reported: false,
+        });
+    }
+
+    /// Stops playing, leaving the sprite with its own source region.
+    pub fn stop(&mut self) {
+        self.playback = None;
+    }
+
+    /// Name of the clip being played.
+    pub fn clip(&self) -> Option<&str> {
+        self.playback
+            .as_ref()
+            .map(|playback| playback.clip.as_str())
+    }
+
+    /// Index of the current frame within the clip.
+    pub fn frame(&self) -> Option<usize> {
+        self.playback.as_ref().map(|playback| playback.frame)
+    }
+
+    /// The texture region of the current frame.
+    pub fn source(&self) -> Option<Rect> {
+        let playback = self.playback.as_ref()?;
+        let clip = self.clips.get(&playback.clip)?;
+        clip.frames.get(playback.frame).map(|frame| frame.source)
+    }
+
+    /// Whether a clip that plays once reached the end of its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.playback
+            .as_ref()
+            .is_some_and(|playback| playback.finished)
+    }
+
+    /// Advances the current clip by `dt` and calls `report` with the index and event of every
+    /// frame that started showing.
+    fn advance(&mut self, dt: Duration, mut report: impl FnMut(&str, usize, &str)) {
+        let Some(playback) = &mut self.playback else {
+            return;
+        };
+        let Some(clip) = self.clips.get(&playback.clip) else {
+            return;
+        };
+        if clip.frames.is_empty() {
+            return;
+        }
+
+        let mut enter = |playback: &mut Playback| {
+            if let Some(event) = &clip.frames[playback.frame].event {
+                report(&playback.clip, playback.frame, event);
+            }
+        };
+        if !playback.reported {
+            playback.reported = true;
+            enter(playback);
+        }
+        if self.paused || playback.finished {
+            return;
+        }
+        // Clips without any duration would never leave the loop below.
+        if clip.duration().is_zero() {
+            return;
+        }
+
+        playback.frame_time += dt.mul_f32(self.speed.max(0.0));
+        while playback.frame_time >= clip.frames[playback.frame].duration {
+            playback.frame_time -= clip.frames[playback.frame].duration;
+            match next_frame(clip, playback) {
+                Some(frame) => {
+                    playback.frame = frame;
+                    enter(playback);
+                }
+                None => {
+                    playback.finished = true;
+                    playback.frame_time = Duration::ZERO;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// The frame after the current one, or `None` if a clip that plays once has ended.
+fn next_frame(clip: &AnimationClip, playback: &mut Playback) -> Option<usize> {
+    let last = clip.frames.len() - 1;
+    match clip.loop_mode {
+        LoopMode::Once => (playback.frame < last).then_some(playback.frame + 1),
+        LoopMode::Loop => Some(if playback.frame < last {
+            playback.frame + 1
+        } else {
+            0
+        }),
+        LoopMode::PingPong => {
+            if last == 0 {
+                return Some(0);
+            }
+            if playback.backwards && playback.frame == 0 {
+                playback.backwards = false;
+            } else if !playback.backwards && playback.frame == last {
+                playback.backwards = true;
+            }
+            Some(if playback.backwards {
+                playback.frame - 1
+            } else {
+                playback.frame + 1
+            })
+        }
+    }
+}
+
+/// Reported by [`update_animators`] when a frame with an event starts showing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub clip: String,
+    pub frame: usize,
+    pub event: String,
+}
+
+/// Advances every entity's [`Animator`] by `dt` and returns the events of the frames that
+/// started showing, in order.
+pub fn update_animators(world: &mut World, dt: Duration) -> Vec<AnimationEvent> {
+    let mut events = Vec::new();
+    for (entity, animator) in world.query_mut::<&mut Animator>() {
+        animator.advance(dt, |clip, frame, event| {
+            events.push(AnimationEvent {
+                entity,
+                clip: clip.to_string(),
+                frame,
+                event: event.to_string(),
+            });
+        });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_sources(animator: &mut Animator, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                animator.advance(Duration::from_millis(100), |_, _, _| {});
+                animator.frame().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn grid_clips_follow_loop_modes() {
+        let mut clip =
+            AnimationClip::from_grid(Vec2::new(16.0, 32.0), 4, 3..6, Duration::from_millis(100));
+        assert_eq!(
+            clip.frames[1].source,
+            Rect::new(Vec2::new(0.0, 32.0), Vec2::new(16.0, 32.0))
+        );
+
+        let mut animator = Animator::new(HashMap::from([("walk".to_string(), clip.clone())]));
+        animator.play("walk");
+        assert_eq!(frame_sources(&mut animator, 4), [1, 2, 0, 1]);
+
+        clip.loop_mode = LoopMode::PingPong;
+        animator.clips.insert("walk".to_string(), clip.clone());
+        animator.stop();
+        animator.play("walk");
+        assert_eq!(frame_sources(&mut animator, 5), [1, 2, 1, 0, 1]);
+
+        clip.loop_mode = LoopMode::Once;
+        animator.clips.insert("walk".to_string(), clip);
+        animator.stop();
+        animator.play("walk");
+        assert_eq!(frame_sources(&mut animator, 3), [1, 2, 2]);
+        assert!(animator.is_finished());
+    }
+
+    #[test]
+    fn frame_events_are_reported() {
+        let mut clip = AnimationClip::from_grid(Vec2::splat(8.0), 2, 0..4, Duration::ZERO);
+        for (frame, duration) in clip.frames.iter_mut().zip([50, 50, 100, 100]) {
+            frame.duration = Duration::from_millis(duration);
+        }
+        clip.frames[0].event = Some("start".to_string());
+        clip.frames[2].event = Some("step".to_string());
+
+        let mut world = World::new();
+        let mut animator = Animator::new(HashMap::from([("run".to_string(), clip)]));
+        animator.play("run");
+        let entity = world.spawn((animator,));
+
+        let names = |events: Vec<AnimationEvent>| {
+            events
+                .into_iter()
+                .map(|event| (event.frame, event.event))
+                .collect::<Vec<_>>()
+        };
+        // Skipping over frames still reports their events.
+        let events = update_animators(&mut world, Duration::from_millis(120));
+        assert_eq!(
+            names(events),
+            [(0, "start".to_string()), (2, "step".to_string())]
+        );
+        assert_eq!(world.get::<&Animator>(entity).unwrap().frame(), Some(2));
+
+        let events = update_animators(&mut world, Duration::from_millis(200));
+        assert_eq!(names(events), [(0, "start".to_string())]);
+    }
+
+    #[test]
+    fn aseprite_tags_become_clips() {
+        let json = br#"{
+            "frames": {
+                "hero 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 32, "h": 32 }, "duration": 80 },
+                "hero 1.aseprite": { "frame": { "x": 32, "y": 0, "w": 32, "h": 32 }, "duration": 80 },
+                "hero 2.aseprite": { "frame": { "x": 64, "y": 0, "w": 32, "h": 32 }, "duration": 120 }
+            },
+            "meta": {
+                "frameTags": [
+                    { "name": "idle", "from": 0, "to": 0, "direction": "forward" },
+                    { "name": "walk", "from": 1, "to": 2, "direction": "pingpong" },
+                    { "name": "die", "from": 0, "to": 2, "direction": "reverse", "repeat": "1" }
+                ]
+            }
+        }"#;
+        let clips = parse_aseprite(json).unwrap();
+
+        assert_eq!(clips["idle"].frames.len(), 1);
+        assert_eq!(clips["walk"].loop_mode, LoopMode::PingPong);
+        assert_eq!(clips["walk"].duration(), Duration::from_millis(200));
+        assert_eq!(clips["die"].loop_mode, LoopMode::Once);
+        assert_eq!(clips["die"].frames[0].source.position, Vec2::new(64.0, 0.0));
+
+        assert!(matches!(
+            parse_aseprite(br#"{ "frames": [{ "duration": 100 }] }"#),
+            Err(SpriteSheetError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn aseprite_sheets_load_their_image() {
+        let directory =
+            std::env::temp_dir().join(format!("papercut-aseprite-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut image = Vec::new();
+        let mut encoder = png::Encoder::new(&mut image, 2, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[255; 8]).unwrap();
+        drop(writer);
+        std::fs::write(directory.join("sheet.png"), image).unwrap();
+
+        let json = br#"{
+            "frames": [
+                { "frame": { "x": 0, "y": 0, "w": 1, "h": 1 }, "duration": 100 },
+                { "frame": { "x": 1, "y": 0, "w": 1, "h": 1 }, "duration": 100 }
+            ],
+            "meta": { "image": "sheet.png" }
+        }"#;
+        std::fs::write(directory.join("sheet.json"), json).unwrap();
+        let sheet = load_aseprite(directory.join("sheet.json"));
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let sheet = sheet.unwrap();
+        assert_eq!(sheet.texture.size(), glam::UVec2::new(2, 1));
+        assert_eq!(sheet.clips["default"].frames.len(), 2);
+    }
+}
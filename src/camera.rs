@@ -1,25 +1,49 @@
-use glam::Mat4;
+use glam::{Mat4, Vec2};
+
+use crate::graphics::Rect;
+
+/// Maximum number of cameras the window can be split between, see
+/// [`crate::Context::set_cameras`].
+pub const MAX_CAMERAS: usize = 8;
 
 #[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Camera {
     width: f32,
     height: f32,
+    surface_size: Vec2,
+    position: Vec2,
+    zoom: f32,
+    viewport: Rect,
     view: Mat4,
     projection: Mat4,
 }
 
 impl Camera {
+    /// Depth range covered by the projection; `Transform::z_index` values outside of it are
+    /// clipped.
+    pub const Z_NEAR: f32 = -1000.0;
+    pub const Z_FAR: f32 = 1000.0;
+
+    /// A camera covering a surface of the given size.
     pub fn new(width: f32, height: f32) -> Self {
-        let projection = glam::Mat4::orthographic_lh(0.0, width, 0.0, height, -1.0, 10.0);
+        let projection =
+            glam::Mat4::orthographic_lh(0.0, width, 0.0, height, Self::Z_NEAR, Self::Z_FAR);
 
         Self {
             width,
             height,
+            surface_size: Vec2::new(width, height),
+            position: Vec2::ZERO,
+            zoom: 1.0,
+            viewport: Rect::new(Vec2::ZERO, Vec2::ONE),
             view: Mat4::IDENTITY,
             projection,
         }
     }
 
+    /// Width of the camera's viewport in pixels. Unzoomed, this is also how much of the world it
+    /// shows.
     pub fn width(&self) -> f32 {
         self.width
     }
@@ -28,29 +52,77 @@ impl Camera {
         self.height
     }
 
+    /// World position shown at the bottom left of the viewport.
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+
+    /// How many pixels a world unit covers. Zooming keeps the world position at the bottom left
+    /// of the viewport in place.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Sets the zoom, which must be positive.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        if zoom > 0.0 {
+            self.zoom = zoom;
+            self.projection = self.compute_projection();
+        } else {
+            log::warn!("ignoring camera zoom {}, which is not positive", zoom);
+        }
+    }
+
+    /// Size of the part of the world the camera shows.
+    pub fn world_size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height) / self.zoom
+    }
+
+    /// The part of the surface the camera renders to, normalized so that (0, 0) is the top left
+    /// and (1, 1) the bottom right of the surface.
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+        self.resize(self.surface_size.x, self.surface_size.y);
+    }
+
+    /// The viewport in pixels from the top left of the surface, clamped to the surface.
+    pub fn viewport_in_pixels(&self) -> Rect {
+        let position = self.viewport.position.clamp(Vec2::ZERO, Vec2::ONE);
+        let end = (self.viewport.position + self.viewport.size).clamp(position, Vec2::ONE);
+        Rect::new(
+            position * self.surface_size,
+            (end - position) * self.surface_size,
+        )
+    }
+
+    /// Adapts the camera to a new surface size, keeping its normalized viewport.
     pub fn resize(&mut self, width: f32, height: f32) {
-        let projection = glam::Mat4::orthographic_lh(0.0, width, 0.0, height, -1.0, 10.0);
+        self.surface_size = Vec2::new(width, height);
+        self.width = width * self.viewport.size.x;
+        self.height = height * self.viewport.size.y;
 
-        self.width = width;
-        self.height = height;
-        self.projection = projection;
+        self.projection = self.compute_projection();
+    }
+
+    fn compute_projection(&self) -> Mat4 {
+        let size = self.world_size();
+        glam::Mat4::orthographic_lh(0.0, size.x, 0.0, size.y, Self::Z_NEAR, Self::Z_FAR)
     }
 
     pub fn get_view(&self) -> Mat4 {
-        // Just use some jankey values for look at for now.
-        // let view = glam::Mat4::look_at_lh(
-        //     glam::Vec3::new(-200.0, -200.0, -1.0),
-        //     glam::Vec3::new(-200.0, -200.0, 0.0),
-        //     glam::Vec3::Y,
-        // );
-
-        let view = glam::Mat4::look_at_lh(
-            glam::Vec3::new(0.0, 0.0, -1.0),
-            glam::Vec3::new(0.0, 0.0, 0.0),
+        glam::Mat4::look_at_lh(
+            self.position.extend(-1.0),
+            self.position.extend(0.0),
             glam::Vec3::Y,
-        );
-
-        view
+        )
     }
 
     pub fn get_projection(&self) -> Mat4 {
@@ -0,0 +1,384 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread::JoinHandle,
+};
+
+use wgpu::{BindGroup, BindGroupLayout, Buffer, CommandEncoder, RenderPipeline, TextureView};
+
+use crate::renderer::GraphicsDevice;
+
+/// A frame copied into a buffer, waiting for the GPU to finish so it can be read back.
+struct PendingCapture {
+    buffer: Buffer,
+    paths: Vec<PathBuf>,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    mapped: Option<Receiver<Result<(), wgpu::BufferAsyncError>>>,
+}
+
+struct CaptureTarget {
+    texture: wgpu::Texture,
+    view: TextureView,
+    bind_group: BindGroup,
+}
+
+/// Reads rendered frames back from the GPU and writes them as PNG files.
+///
+/// The swapchain may not support being copied from, so frames that are captured are rendered
+/// into an intermediate texture which is then copied into a buffer and blitted to the frame.
+pub(crate) struct FrameCapture {
+    format: wgpu::TextureFormat,
+    bind_group_layout: BindGroupLayout,
+    sampler: wgpu::Sampler,
+    pipeline: RenderPipeline,
+    target: CaptureTarget,
+    pending: Vec<PendingCapture>,
+    writers: Vec<JoinHandle<()>>,
+}
+
+impl FrameCapture {
+    pub fn new(device: &wgpu::Device, surface_config: &wgpu::SurfaceConfiguration) -> Self {
+        let format = surface_config.format;
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("capture bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("capture pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("capture sampler"),
+            ..Default::default()
+        });
+
+        let source = format!(
+            "{}{}",
+            include_str!("./../shaders/post/fullscreen.wgsl"),
+            include_str!("./../shaders/post/blit.wgsl")
+        );
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("blit"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("blit"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let target =
+            Self::create_target(device, surface_config, format, &bind_group_layout, &sampler);
+
+        Self {
+            format,
+            bind_group_layout,
+            sampler,
+            pipeline,
+            target,
+            pending: Vec::new(),
+            writers: Vec::new(),
+        }
+    }
+
+    pub fn resize(&mut self, device: &GraphicsDevice) {
+        self.target = Self::create_target(
+            &device.device,
+            &device.config,
+            self.format,
+            &self.bind_group_layout,
+            &self.sampler,
+        );
+    }
+
+    fn create_target(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+        bind_group_layout: &BindGroupLayout,
+        sampler: &wgpu::Sampler,
+    ) -> CaptureTarget {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("capture target"),
+            size: wgpu::Extent3d {
+                width: surface_config.width,
+                height: surface_config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("capture bind group"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        CaptureTarget {
+            texture,
+            view,
+            bind_group,
+        }
+    }
+
+    /// Where a frame that is going to be captured should be rendered, or `None` if frames in the
+    /// surface format cannot be written as PNG.
+    pub fn target(&self) -> Option<&TextureView> {
+        rgba_swizzle(self.format).map(|_| &self.target.view)
+    }
+
+    /// Copies the frame rendered into [`Self::target`] to `frame` and schedules writing it to
+    /// each of `paths` once the encoder has been submitted, see [`Self::after_submit`].
+    pub fn capture(
+        &mut self,
+        device: &GraphicsDevice,
+        encoder: &mut CommandEncoder,
+        frame: &TextureView,
+        paths: Vec<PathBuf>,
+    ) {
+        if rgba_swizzle(self.format).is_none() {
+            log::warn!("frames in {:?} cannot be saved as PNG", self.format);
+            return;
+        }
+        let target = &self.target;
+
+        let width = device.config.width;
+        let height = device.config.height;
+        let unpadded_bytes_per_row = width * 4;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(alignment) * alignment;
+
+        let buffer = device.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            target.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("blit pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &target.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        self.pending.push(PendingCapture {
+            buffer,
+            paths,
+            width,
+            height,
+            padded_bytes_per_row,
+            mapped: None,
+        });
+    }
+
+    /// Starts reading back the frames captured in the submitted encoder.
+    pub fn after_submit(&mut self) {
+        for capture in self.pending.iter_mut().filter(|c| c.mapped.is_none()) {
+            let (sender, receiver) = mpsc::channel();
+            capture
+                .buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    // The receiver is gone if the capture was dropped, which is fine.
+                    let _ = sender.send(result);
+                });
+            capture.mapped = Some(receiver);
+        }
+    }
+
+    /// Writes the frames the GPU has finished with, without blocking. With `wait`, blocks until
+    /// every captured frame has been written.
+    pub fn poll(&mut self, device: &wgpu::Device, wait: bool) {
+        if self.pending.is_empty() && !wait {
+            return;
+        }
+
+        device.poll(if wait {
+            wgpu::Maintain::Wait
+        } else {
+            wgpu::Maintain::Poll
+        });
+
+        let swizzle = match rgba_swizzle(self.format) {
+            Some(swizzle) => swizzle,
+            None => return,
+        };
+
+        let mut i = 0;
+        while i < self.pending.len() {
+            let result = match &self.pending[i].mapped {
+                Some(receiver) => receiver.try_recv(),
+                None => {
+                    i += 1;
+                    continue;
+                }
+            };
+
+            match result {
+                Ok(Ok(())) => {
+                    let capture = self.pending.remove(i);
+                    let pixels = capture.read_pixels(swizzle);
+                    let (width, height) = (capture.width, capture.height);
+                    self.writers.push(std::thread::spawn(move || {
+                        for path in capture.paths {
+                            if let Err(e) = write_png(&path, width, height, &pixels) {
+                                log::error!("failed to write {}: {}", path.display(), e);
+                            }
+                        }
+                    }));
+                }
+                Ok(Err(e)) => {
+                    self.pending.remove(i);
+                    log::error!("failed to read back a captured frame: {}", e);
+                }
+                Err(_) => i += 1,
+            }
+        }
+
+        if wait {
+            for writer in self.writers.drain(..) {
+                let _ = writer.join();
+            }
+        } else {
+            self.writers.retain(|writer| !writer.is_finished());
+        }
+    }
+}
+
+impl PendingCapture {
+    /// Copies the mapped buffer into tightly packed RGBA rows. Alpha is made opaque since the
+    /// window ignores it too.
+    fn read_pixels(&self, swizzle: [usize; 3]) -> Vec<u8> {
+        let data = self.buffer.slice(..).get_mapped_range();
+        let mut pixels = Vec::with_capacity((self.width * self.height * 4) as usize);
+        for row in data
+            .chunks(self.padded_bytes_per_row as usize)
+            .take(self.height as usize)
+        {
+            for texel in row[..(self.width * 4) as usize].chunks_exact(4) {
+                pixels.extend([texel[swizzle[0]], texel[swizzle[1]], texel[swizzle[2]], 255]);
+            }
+        }
+        drop(data);
+        self.buffer.unmap();
+
+        pixels
+    }
+}
+
+/// Positions of the red, green and blue channels in a texel of `format`, for the 8-bit formats
+/// the surface can use.
+fn rgba_swizzle(format: wgpu::TextureFormat) -> Option<[usize; 3]> {
+    match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => Some([0, 1, 2]),
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => Some([2, 1, 0]),
+        _ => None,
+    }
+}
+
+fn write_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> Result<(), png::EncodingError> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)
+}
@@ -1,6 +1,18 @@
+use std::sync::Arc;
+
 use glam::{Mat4, Vec2, Vec3};
+use lyon::lyon_tessellation::VertexBuffers;
 
-use crate::graphics::{CircleShape, LineShape, PolygonShape, RectangleShape};
+use crate::{
+    error::GeometryError,
+    graphics::{
+        ArcShape, BlendMode, CircleShape, EllipseShape, Geometry, GeometryVertex, LineShape,
+        PathShape, PolygonShape, PolylineShape, Rect, RectangleShape, RingShape,
+        RoundedRectangleShape, SectorShape, SpriteShape, Tessellator,
+    },
+    material::MaterialInstance,
+    texture::Texture,
+};
 
 #[derive(Debug, Clone)]
 pub struct Tag {
@@ -13,6 +25,9 @@ pub struct Transform {
     pub rotation: f32,
     pub scale: Vec2,
     pub origin: Vec2,
+    /// Draw order; shapes with a higher z-index are drawn on top. Must lie within the
+    /// camera's depth range, see [`crate::camera::Camera::Z_NEAR`].
+    pub z_index: f32,
 }
 
 impl Default for Transform {
@@ -22,6 +37,7 @@ impl Default for Transform {
             rotation: 0.0,
             scale: Vec2::new(1.0, 1.0),
             origin: Vec2::new(0.0, 0.0),
+            z_index: 0.0,
         }
     }
 }
@@ -47,10 +63,13 @@ pub fn compute_transformation_matrix(t: &Transform) -> Mat4 {
     transform
 }
 
+/// Maps world positions back into the local space of a shape drawn with `t`. The origin is
+/// added after undoing the rotation since it is subtracted from the scaled local position.
 pub fn compute_inverse_transformation_matrix(t: &Transform) -> Mat4 {
     let mut transform = Mat4::from_scale(Vec3::from((1.0 / t.scale, 0.0)));
+    transform *= Mat4::from_translation(Vec3::from((t.origin, 0.0)));
     transform *= Mat4::from_rotation_z(t.rotation.to_radians());
-    transform *= Mat4::from_translation(Vec3::from((t.origin - t.translation, 0.0)));
+    transform *= Mat4::from_translation(Vec3::from((-t.translation, 0.0)));
     transform
 }
 
@@ -60,4 +79,285 @@ pub enum Drawable {
     Line(LineShape),
     Polygon(PolygonShape),
     Rect(RectangleShape),
+    Sprite(SpriteShape),
+    Path(PathShape),
+    RoundedRect(RoundedRectangleShape),
+    Ellipse(EllipseShape),
+    Arc(ArcShape),
+    Sector(SectorShape),
+    Ring(RingShape),
+    Polyline(PolylineShape),
+}
+
+impl Drawable {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        match self {
+            Drawable::Circle(circle) => circle.vertices(),
+            Drawable::Line(line) => line.vertices(),
+            Drawable::Polygon(polygon) => polygon.vertices(),
+            Drawable::Rect(rect) => rect.vertices(),
+            Drawable::Sprite(sprite) => sprite.vertices(),
+            Drawable::Path(path) => path.vertices(),
+            Drawable::RoundedRect(rect) => rect.vertices(),
+            Drawable::Ellipse(ellipse) => ellipse.vertices(),
+            Drawable::Arc(arc) => arc.vertices(),
+            Drawable::Sector(sector) => sector.vertices(),
+            Drawable::Ring(ring) => ring.vertices(),
+            Drawable::Polyline(polyline) => polyline.vertices(),
+        }
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        match self {
+            Drawable::Circle(circle) => circle.indices(),
+            Drawable::Line(line) => line.indices(),
+            Drawable::Polygon(polygon) => polygon.indices(),
+            Drawable::Rect(rect) => rect.indices(),
+            Drawable::Sprite(sprite) => sprite.indices(),
+            Drawable::Path(path) => path.indices(),
+            Drawable::RoundedRect(rect) => rect.indices(),
+            Drawable::Ellipse(ellipse) => ellipse.indices(),
+            Drawable::Arc(arc) => arc.indices(),
+            Drawable::Sector(sector) => sector.indices(),
+            Drawable::Ring(ring) => ring.indices(),
+            Drawable::Polyline(polyline) => polyline.indices(),
+        }
+    }
+
+    /// The geometry to draw at `screen_scale` times the drawable's size if its own geometry is
+    /// too coarse or needlessly fine there.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        match self {
+            Drawable::Circle(circle) => circle.lod(screen_scale),
+            Drawable::Line(line) => line.lod(screen_scale),
+            Drawable::Polygon(polygon) => polygon.lod(screen_scale),
+            Drawable::Rect(rect) => rect.lod(screen_scale),
+            Drawable::Sprite(_) => None,
+            Drawable::Path(path) => path.lod(screen_scale),
+            Drawable::RoundedRect(rect) => rect.lod(screen_scale),
+            Drawable::Ellipse(ellipse) => ellipse.lod(screen_scale),
+            Drawable::Arc(arc) => arc.lod(screen_scale),
+            Drawable::Sector(sector) => sector.lod(screen_scale),
+            Drawable::Ring(ring) => ring.lod(screen_scale),
+            Drawable::Polyline(polyline) => polyline.lod(screen_scale),
+        }
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        match self {
+            Drawable::Circle(circle) => circle.blend_mode,
+            Drawable::Line(line) => line.blend_mode,
+            Drawable::Polygon(polygon) => polygon.blend_mode,
+            Drawable::Rect(rect) => rect.blend_mode,
+            Drawable::Sprite(sprite) => sprite.blend_mode,
+            Drawable::Path(path) => path.blend_mode,
+            Drawable::RoundedRect(rect) => rect.blend_mode,
+            Drawable::Ellipse(ellipse) => ellipse.blend_mode,
+            Drawable::Arc(arc) => arc.blend_mode,
+            Drawable::Sector(sector) => sector.blend_mode,
+            Drawable::Ring(ring) => ring.blend_mode,
+            Drawable::Polyline(polyline) => polyline.blend_mode,
+        }
+    }
+
+    pub fn material(&self) -> Option<&MaterialInstance> {
+        match self {
+            Drawable::Circle(circle) => circle.material.as_ref(),
+            Drawable::Line(line) => line.material.as_ref(),
+            Drawable::Polygon(polygon) => polygon.material.as_ref(),
+            Drawable::Rect(rect) => rect.material.as_ref(),
+            Drawable::Sprite(sprite) => sprite.material.as_ref(),
+            Drawable::Path(path) => path.material.as_ref(),
+            Drawable::RoundedRect(rect) => rect.material.as_ref(),
+            Drawable::Ellipse(ellipse) => ellipse.material.as_ref(),
+            Drawable::Arc(arc) => arc.material.as_ref(),
+            Drawable::Sector(sector) => sector.material.as_ref(),
+            Drawable::Ring(ring) => ring.material.as_ref(),
+            Drawable::Polyline(polyline) => polyline.material.as_ref(),
+        }
+    }
+
+    pub fn texture(&self) -> Option<&Texture> {
+        match self {
+            Drawable::Sprite(sprite) => sprite.texture.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The drawable's outline as a polygon in local coordinates, used to cast shadows. Lines,
+    /// arcs and polylines have no area and therefore no outline; paths use their first subpath
+    /// if it is closed.
+    pub fn outline(&self) -> Option<Vec<Vec2>> {
+        match self {
+            Drawable::Circle(circle) => Some(circle.outline()),
+            Drawable::Line(_) => None,
+            Drawable::Polygon(polygon) => Some(polygon.outline()),
+            Drawable::Rect(rect) => Some(rect.outline()),
+            Drawable::Sprite(sprite) => Some(sprite.outline()),
+            Drawable::Path(path) => path.outline(),
+            Drawable::RoundedRect(rect) => Some(rect.outline()),
+            Drawable::Ellipse(ellipse) => Some(ellipse.outline()),
+            Drawable::Arc(_) => None,
+            Drawable::Sector(sector) => Some(sector.outline()),
+            Drawable::Ring(ring) => Some(ring.outline()),
+            Drawable::Polyline(_) => None,
+        }
+    }
+
+    /// Bounding box of the drawable's geometry in local coordinates, including its outline.
+    /// `None` if the drawable has no geometry, e.g. before it was first updated.
+    pub fn local_bounds(&self) -> Option<Rect> {
+        bounding_rect(self.vertices().iter().map(|v| v.position()))
+    }
+
+    /// Bounding box of the drawable's geometry in world coordinates when drawn with `transform`.
+    pub fn world_bounds(&self, transform: &Transform) -> Option<Rect> {
+        let t = compute_transformation_matrix(transform);
+        bounding_rect(
+            self.vertices()
+                .iter()
+                .map(|v| t.transform_point3(v.position().extend(0.0)).truncate()),
+        )
+    }
+
+    /// Whether `world_point` lies on the drawable drawn with `transform`, including its outline
+    /// and edges. Tests against the tessellated triangles, so curves are as accurate as the
+    /// tessellator's tolerance.
+    pub fn contains_point(&self, world_point: Vec2, transform: &Transform) -> bool {
+        let point = compute_inverse_transformation_matrix(transform)
+            .transform_point3(world_point.extend(0.0))
+            .truncate();
+        let inside_bounds = self.local_bounds().is_some_and(|bounds| {
+            point.cmpge(bounds.position).all() && point.cmple(bounds.position + bounds.size).all()
+        });
+        if !inside_bounds {
+            return false;
+        }
+
+        let vertices = self.vertices();
+        self.indices().chunks_exact(3).any(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] as usize].position());
+            triangle_contains(a, b, c, point)
+        })
+    }
+
+    /// Whether the drawable can be drawn in the opaque pass, i.e. it is alpha blended, untextured,
+    /// drawn without a material and every vertex is fully opaque.
+    pub fn is_opaque(&self) -> bool {
+        self.blend_mode() == BlendMode::Alpha
+            && !matches!(self, Drawable::Sprite(_))
+            && self.material().is_none()
+            && self.vertices().iter().all(|v| v.color().a >= 1.0)
+    }
+}
+
+impl Geometry for Drawable {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        match self {
+            Drawable::Circle(circle) => circle.update(tessellator),
+            Drawable::Line(line) => line.update(tessellator),
+            Drawable::Polygon(polygon) => polygon.update(tessellator),
+            Drawable::Rect(rect) => rect.update(tessellator),
+            Drawable::Sprite(sprite) => sprite.update(tessellator),
+            Drawable::Path(path) => path.update(tessellator),
+            Drawable::RoundedRect(rect) => rect.update(tessellator),
+            Drawable::Ellipse(ellipse) => ellipse.update(tessellator),
+            Drawable::Arc(arc) => arc.update(tessellator),
+            Drawable::Sector(sector) => sector.update(tessellator),
+            Drawable::Ring(ring) => ring.update(tessellator),
+            Drawable::Polyline(polyline) => polyline.update(tessellator),
+        }
+    }
+}
+
+fn bounding_rect(mut points: impl Iterator<Item = Vec2>) -> Option<Rect> {
+    let first = points.next()?;
+    let (min, max) = points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+    Some(Rect::new(min, max - min))
+}
+
+/// Whether `p` lies inside or on the edges of the triangle `a`, `b`, `c` of either winding.
+/// Degenerate triangles contain nothing.
+fn triangle_contains(a: Vec2, b: Vec2, c: Vec2, p: Vec2) -> bool {
+    if (b - a).perp_dot(c - a) == 0.0 {
+        return false;
+    }
+
+    let d1 = (b - a).perp_dot(p - a);
+    let d2 = (c - b).perp_dot(p - b);
+    let d3 = (a - c).perp_dot(p - c);
+    let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_negative && has_positive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{Material, MaterialHandle, MaterialInstance};
+
+    fn square() -> Drawable {
+        let mut rect = RectangleShape::default();
+        rect.size = Vec2::new(100.0, 50.0);
+        rect.update(&mut Tessellator::new(0.1)).unwrap();
+        Drawable::Rect(rect)
+    }
+
+    #[test]
+    fn inverse_transformation_undoes_transformation() {
+        let transform = Transform {
+            translation: Vec2::new(300.0, 200.0),
+            rotation: 30.0,
+            scale: Vec2::new(2.0, 0.5),
+            origin: Vec2::new(50.0, 25.0),
+            z_index: 0.0,
+        };
+        let t = compute_transformation_matrix(&transform);
+        let inverse = compute_inverse_transformation_matrix(&transform);
+        let point = Vec3::new(12.0, -7.0, 0.0);
+        let round_trip = inverse.transform_point3(t.transform_point3(point));
+        assert!(round_trip.abs_diff_eq(point, 1e-3), "{}", round_trip);
+    }
+
+    #[test]
+    fn bounds_follow_transform() {
+        let drawable = square();
+        assert_eq!(
+            drawable.local_bounds(),
+            Some(Rect::new(Vec2::ZERO, Vec2::new(100.0, 50.0)))
+        );
+
+        let mut transform = Transform::from_position(10.0, 20.0);
+        transform.rotation = 90.0;
+        let bounds = drawable.world_bounds(&transform).unwrap();
+        assert!(bounds.position.abs_diff_eq(Vec2::new(10.0, -80.0), 1e-3));
+        assert!(bounds.size.abs_diff_eq(Vec2::new(50.0, 100.0), 1e-3));
+    }
+
+    #[test]
+    fn contains_point_with_rotation_and_origin() {
+        let drawable = square();
+        let mut transform = Transform::from_position(200.0, 200.0);
+        transform.origin = Vec2::new(50.0, 25.0);
+        transform.rotation = 90.0;
+
+        // Rotated a quarter turn about its center, the square is 50 wide and 100 tall.
+        assert!(drawable.contains_point(Vec2::new(200.0, 200.0), &transform));
+        assert!(drawable.contains_point(Vec2::new(180.0, 240.0), &transform));
+        assert!(!drawable.contains_point(Vec2::new(240.0, 200.0), &transform));
+        assert!(!Drawable::Rect(RectangleShape::default())
+            .contains_point(Vec2::ZERO, &Transform::default()));
+    }
+
+    #[test]
+    fn shapes_with_a_material_are_translucent() {
+        let mut drawable = square();
+        assert!(drawable.is_opaque());
+
+        if let Drawable::Rect(rect) = &mut drawable {
+            let material = Material::new("flat", include_str!("./../shaders/geometry.wgsl"));
+            rect.material = Some(MaterialInstance::new(MaterialHandle::new(material)));
+        }
+        assert!(!drawable.is_opaque());
+    }
 }
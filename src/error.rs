@@ -0,0 +1,260 @@
+use std::fmt;
+
+use lyon::tessellation::TessellationError;
+use winit::error::OsError;
+
+/// Errors that stop the game from starting or keep it from running.
+#[derive(Debug)]
+pub enum PapercutError {
+    /// No monitor was found to place the window on.
+    NoMonitor,
+    /// Exclusive fullscreen was requested but the monitor reports no video modes.
+    NoVideoMode,
+    /// The window could not be created.
+    Window(OsError),
+    /// No graphics adapter is compatible with the window surface.
+    NoAdapter,
+    /// The graphics adapter refused to create a device.
+    RequestDevice(wgpu::RequestDeviceError),
+    /// The window surface supports no formats the adapter can render to.
+    NoSurfaceFormat,
+    /// A render pipeline of the built-in shaders failed validation.
+    PipelineCreation(wgpu::Error),
+    /// The GPU ran out of memory while acquiring a frame.
+    OutOfMemory,
+}
+
+impl fmt::Display for PapercutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PapercutError::NoMonitor => write!(f, "no monitors found"),
+            PapercutError::NoVideoMode => write!(f, "no video modes found"),
+            PapercutError::Window(e) => write!(f, "failed to create window: {}", e),
+            PapercutError::NoAdapter => write!(f, "no compatible graphics adapter found"),
+            PapercutError::RequestDevice(e) => write!(f, "failed to create graphics device: {}", e),
+            PapercutError::NoSurfaceFormat => write!(f, "no supported surface format found"),
+            PapercutError::PipelineCreation(e) => write!(f, "failed to create pipeline: {}", e),
+            PapercutError::OutOfMemory => write!(f, "out of GPU memory"),
+        }
+    }
+}
+
+impl std::error::Error for PapercutError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PapercutError::Window(e) => Some(e),
+            PapercutError::RequestDevice(e) => Some(e),
+            PapercutError::PipelineCreation(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<OsError> for PapercutError {
+    fn from(e: OsError) -> Self {
+        PapercutError::Window(e)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for PapercutError {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        PapercutError::RequestDevice(e)
+    }
+}
+
+/// Errors that keep a shape from being tessellated, see [`crate::graphics::Geometry::update`].
+/// The shape is left without geometry instead of keeping its previous one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeometryError {
+    /// A shape parameter is out of range, e.g. a polygon with fewer than 3 points or a size
+    /// that is not finite.
+    InvalidShape { shape: &'static str, reason: String },
+    /// The tessellator rejected the shape's geometry, e.g. because it needs more vertices than
+    /// a shape can hold.
+    Tessellation(TessellationError),
+}
+
+impl fmt::Display for GeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeometryError::InvalidShape { shape, reason } => {
+                write!(f, "invalid {}: {}", shape, reason)
+            }
+            GeometryError::Tessellation(e) => write!(f, "failed to tessellate shape: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GeometryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GeometryError::Tessellation(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<TessellationError> for GeometryError {
+    fn from(e: TessellationError) -> Self {
+        GeometryError::Tessellation(e)
+    }
+}
+
+/// Errors from parsing a hex color, see [`crate::graphics::Color::from_hex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// The color has this many digits instead of 3, 4, 6 or 8.
+    InvalidLength(usize),
+    /// The color contains a character that is not a hex digit.
+    InvalidDigit(char),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseColorError::InvalidLength(len) => {
+                write!(f, "hex color has {} digits, expected 3, 4, 6 or 8", len)
+            }
+            ParseColorError::InvalidDigit(c) => write!(f, "invalid hex digit {:?} in color", c),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Errors from loading an image, see [`crate::texture::Texture::from_png`].
+#[derive(Debug)]
+pub enum TextureError {
+    Io(std::io::Error),
+    /// The image is not a valid PNG.
+    Decode(png::DecodingError),
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Io(e) => write!(f, "failed to read image: {}", e),
+            TextureError::Decode(e) => write!(f, "failed to decode image: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureError::Io(e) => Some(e),
+            TextureError::Decode(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for TextureError {
+    fn from(e: std::io::Error) -> Self {
+        TextureError::Io(e)
+    }
+}
+
+impl From<png::DecodingError> for TextureError {
+    fn from(e: png::DecodingError) -> Self {
+        TextureError::Decode(e)
+    }
+}
+
+/// Errors from loading a sprite sheet, see [`crate::animation::load_aseprite`].
+#[derive(Debug)]
+pub enum SpriteSheetError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    /// The JSON is not an Aseprite sprite sheet, e.g. because a frame has no region.
+    Invalid(String),
+    /// The sheet's image could not be loaded.
+    Texture(TextureError),
+}
+
+impl fmt::Display for SpriteSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpriteSheetError::Io(e) => write!(f, "failed to read sprite sheet: {}", e),
+            SpriteSheetError::Parse(e) => write!(f, "failed to parse sprite sheet: {}", e),
+            SpriteSheetError::Invalid(reason) => write!(f, "invalid sprite sheet: {}", reason),
+            SpriteSheetError::Texture(e) => write!(f, "failed to load sprite sheet image: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SpriteSheetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SpriteSheetError::Io(e) => Some(e),
+            SpriteSheetError::Parse(e) => Some(e),
+            SpriteSheetError::Invalid(_) => None,
+            SpriteSheetError::Texture(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for SpriteSheetError {
+    fn from(e: std::io::Error) -> Self {
+        SpriteSheetError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SpriteSheetError {
+    fn from(e: serde_json::Error) -> Self {
+        SpriteSheetError::Parse(e)
+    }
+}
+
+impl From<TextureError> for SpriteSheetError {
+    fn from(e: TextureError) -> Self {
+        SpriteSheetError::Texture(e)
+    }
+}
+
+/// Errors from loading an SVG, see [`crate::svg::load`].
+#[derive(Debug)]
+pub enum SvgError {
+    Io(std::io::Error),
+    Parse(usvg::Error),
+    /// A path could not be tessellated, e.g. because it needs more vertices than a shape can
+    /// hold.
+    Geometry(GeometryError),
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SvgError::Io(e) => write!(f, "failed to read SVG: {}", e),
+            SvgError::Parse(e) => write!(f, "failed to parse SVG: {}", e),
+            SvgError::Geometry(e) => write!(f, "failed to convert SVG: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SvgError::Io(e) => Some(e),
+            SvgError::Parse(e) => Some(e),
+            SvgError::Geometry(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for SvgError {
+    fn from(e: std::io::Error) -> Self {
+        SvgError::Io(e)
+    }
+}
+
+impl From<usvg::Error> for SvgError {
+    fn from(e: usvg::Error) -> Self {
+        SvgError::Parse(e)
+    }
+}
+
+impl From<GeometryError> for SvgError {
+    fn from(e: GeometryError) -> Self {
+        SvgError::Geometry(e)
+    }
+}
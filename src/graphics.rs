@@ -1,20 +1,53 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError, Weak},
+};
+
 use glam::Vec2;
+use i_overlay::{
+    core::overlay_rule::OverlayRule,
+    float::{simplify::SimplifyShape, single::SingleFloatOverlay},
+};
 use lyon::{
-    geom::{point, Box2D, LineSegment},
+    algorithms::measure::{PathMeasurements, SampleType},
+    geom::{point, vector, Angle, Box2D, LineSegment},
     lyon_tessellation::{
         BuffersBuilder, FillOptions, FillTessellator, FillVertexConstructor, StrokeOptions,
         StrokeTessellator, StrokeVertexConstructor, VertexBuffers,
     },
-    path::{Path, Polygon, Winding},
+    path::{
+        builder::{BorderRadii, WithSvg},
+        iterator::PathIterator,
+        path::BuilderImpl,
+        ArcFlags, AttributeStore, Event, PathEvent, Polygon, Winding,
+    },
 };
 
+pub use lyon::path::Path;
+
+use crate::{
+    error::{GeometryError, ParseColorError},
+    material::MaterialInstance,
+    texture::Texture,
+};
+
+/// Turns shapes into triangles. Shapes tessellated with the same parameters by the same
+/// tessellator share one mesh, so spawning many identical shapes tessellates them once.
 pub struct Tessellator {
     tolerance: f32,
     fill_tess: FillTessellator,
     stroke_tess: StrokeTessellator,
+    meshes: HashMap<MeshKey, Weak<Mesh>>,
+    /// Cache size at which meshes no shape uses anymore are dropped from the cache.
+    prune_at: usize,
 }
 
 impl Tessellator {
+    /// A tessellator approximating curves to within `tolerance` pixels for shapes drawn at their
+    /// own size by an unzoomed camera. Shapes drawn larger or smaller are tessellated again for
+    /// their size on screen, see [`crate::Context::draw_shape`].
     pub fn new(tolerance: f32) -> Self {
         let fill_tess = FillTessellator::new();
         let stroke_tess = StrokeTessellator::new();
@@ -23,41 +56,89 @@ impl Tessellator {
             tolerance,
             fill_tess,
             stroke_tess,
+            meshes: HashMap::new(),
+            prune_at: Self::MIN_PRUNE_AT,
         }
     }
 
+    const MIN_PRUNE_AT: usize = 64;
+
     pub fn tolerance(&self) -> f32 {
         self.tolerance
     }
 
+    /// Number of distinct meshes shared by shapes tessellated with this tessellator.
+    pub fn cached_meshes(&self) -> usize {
+        self.meshes
+            .values()
+            .filter(|mesh| mesh.strong_count() > 0)
+            .count()
+    }
+
+    /// The mesh for `tessellation`, shared with earlier shapes that have the same parameters.
+    fn mesh(&mut self, tessellation: Tessellation) -> Result<Arc<Mesh>, GeometryError> {
+        let key = MeshKey::new(&tessellation, self.tolerance);
+        if let Some(mesh) = self.meshes.get(&key).and_then(Weak::upgrade) {
+            return Ok(mesh);
+        }
+
+        let mut geometry = VertexBuffers::new();
+        self.tessellate_into(&tessellation, &mut geometry)?;
+        let mesh = Arc::new(Mesh {
+            geometry,
+            tessellation,
+            tolerance: self.tolerance,
+            levels: Mutex::default(),
+        });
+
+        if self.meshes.len() >= self.prune_at {
+            self.meshes.retain(|_, mesh| mesh.strong_count() > 0);
+            self.prune_at = (self.meshes.len() * 2).max(Self::MIN_PRUNE_AT);
+        }
+        self.meshes.insert(key, Arc::downgrade(&mesh));
+        Ok(mesh)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn tesselate(
         &mut self,
         path: &Path,
-        fill_color: Color,
+        fill_rule: FillRule,
+        fill: &Fill,
         outline_color: Color,
         outline_thickness: f32,
+        outline_style: &StrokeStyle,
+        outline_alignment: OutlineAlignment,
         geometry: &mut VertexBuffers<GeometryVertex, u16>,
-    ) {
-        self.fill_tess
-            .tessellate_path(
+    ) -> Result<(), GeometryError> {
+        // Skip fills that can't be seen, e.g. outline-only shapes.
+        if !matches!(fill, Fill::Solid(color) if color.a <= 0.0) {
+            let first_index = geometry.indices.len();
+            self.fill_tess.tessellate_path(
                 path,
-                &FillOptions::tolerance(self.tolerance)
-                    .with_fill_rule(lyon::tessellation::FillRule::NonZero),
-                &mut BuffersBuilder::new(geometry, GeometryVertexCtor(fill_color))
-                    .with_inverted_winding(),
-            )
-            .unwrap();
+                &FillOptions::tolerance(self.tolerance).with_fill_rule(fill_rule.into()),
+                &mut BuffersBuilder::new(geometry, FillVertexCtor(fill)).with_inverted_winding(),
+            )?;
+            subdivide_fill(geometry, first_index, fill);
+        }
 
         if outline_thickness > 0.0 {
-            self.stroke_tess
-                .tessellate_path(
-                    path,
-                    &StrokeOptions::tolerance(self.tolerance()).with_line_width(outline_thickness),
-                    &mut BuffersBuilder::new(geometry, GeometryVertexCtor(outline_color))
-                        .with_inverted_winding(),
+            // The orientation is stored before dashing, which loses which subpath a dash was
+            // part of.
+            let path = with_orientation(path);
+            let dashed = dash(&path, outline_style, self.tolerance);
+            self.stroke_tess.tessellate_path(
+                dashed.as_ref().unwrap_or(&path),
+                &self.stroke_options(outline_thickness, outline_style),
+                &mut BuffersBuilder::new(
+                    geometry,
+                    GeometryVertexCtor(outline_color, outline_alignment),
                 )
-                .unwrap();
+                .with_inverted_winding(),
+            )?;
         }
+
+        Ok(())
     }
 
     fn tesselate_line(
@@ -65,344 +146,3257 @@ impl Tessellator {
         path: &Path,
         outline_color: Color,
         outline_thickness: f32,
+        outline_style: &StrokeStyle,
         geometry: &mut VertexBuffers<GeometryVertex, u16>,
-    ) {
+    ) -> Result<(), GeometryError> {
         if outline_thickness > 0.0 {
-            self.stroke_tess
-                .tessellate_path(
-                    path,
-                    &StrokeOptions::tolerance(self.tolerance()).with_line_width(outline_thickness),
-                    &mut BuffersBuilder::new(geometry, GeometryVertexLineCtor(outline_color))
-                        .with_inverted_winding(),
-                )
-                .unwrap();
+            let dashed = dash(path, outline_style, self.tolerance);
+            self.stroke_tess.tessellate_path(
+                dashed.as_ref().unwrap_or(path),
+                &self.stroke_options(outline_thickness, outline_style),
+                &mut BuffersBuilder::new(geometry, GeometryVertexLineCtor(outline_color))
+                    .with_inverted_winding(),
+            )?;
         }
+
+        Ok(())
     }
-}
 
-#[derive(Debug, Copy, Clone)]
-pub struct Color {
-    pub r: f32,
-    pub g: f32,
-    pub b: f32,
-    pub a: f32,
-}
+    /// Strokes a path whose attributes are the thickness and color at each point, see
+    /// [`PolylineVertexCtor`].
+    fn tesselate_polyline(
+        &mut self,
+        path: &Path,
+        outline_style: &StrokeStyle,
+        geometry: &mut VertexBuffers<GeometryVertex, u16>,
+    ) -> Result<(), GeometryError> {
+        let dashed = dash(path, outline_style, self.tolerance);
+        self.stroke_tess.tessellate_path(
+            dashed.as_ref().unwrap_or(path),
+            &self
+                .stroke_options(1.0, outline_style)
+                .with_variable_line_width(0),
+            &mut BuffersBuilder::new(geometry, PolylineVertexCtor).with_inverted_winding(),
+        )?;
 
-impl Default for Color {
-    fn default() -> Self {
-        Self::BLACK
+        Ok(())
     }
-}
 
-impl Color {
-    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
-    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+    /// Replaces the contents of `geometry` with `tessellation`, leaving it empty on failure.
+    fn tessellate_into(
+        &mut self,
+        tessellation: &Tessellation,
+        geometry: &mut VertexBuffers<GeometryVertex, u16>,
+    ) -> Result<(), GeometryError> {
+        geometry.vertices.clear();
+        geometry.indices.clear();
 
-    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
-        Self { r, g, b, a }
+        let result = match tessellation {
+            Tessellation::Shape {
+                path,
+                fill_rule,
+                fill,
+                outline_color,
+                outline_thickness,
+                outline_style,
+                outline_alignment,
+            } => self.tesselate(
+                path,
+                *fill_rule,
+                fill,
+                *outline_color,
+                *outline_thickness,
+                outline_style,
+                *outline_alignment,
+                geometry,
+            ),
+            Tessellation::Line {
+                path,
+                color,
+                thickness,
+                style,
+            } => self.tesselate_line(path, *color, *thickness, style, geometry),
+            Tessellation::Polyline { path, style } => {
+                self.tesselate_polyline(path, style, geometry)
+            }
+        };
+        if result.is_err() {
+            geometry.vertices.clear();
+            geometry.indices.clear();
+        }
+        result
     }
 
-    pub const fn to_array(&self) -> [f32; 4] {
-        [self.r, self.g, self.b, self.a]
+    fn stroke_options(&self, line_width: f32, style: &StrokeStyle) -> StrokeOptions {
+        StrokeOptions::tolerance(self.tolerance)
+            .with_line_width(line_width)
+            .with_line_join(style.join.into())
+            .with_line_cap(style.cap.into())
+            .with_miter_limit(style.miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT))
     }
 }
 
-pub trait Geometry {
-    fn update(&mut self, tessellator: &mut Tessellator);
+/// Everything a shape was tessellated from, kept to tessellate it again at another level of
+/// detail.
+#[derive(Debug, Clone)]
+enum Tessellation {
+    Shape {
+        path: Path,
+        fill_rule: FillRule,
+        fill: Fill,
+        outline_color: Color,
+        outline_thickness: f32,
+        outline_style: StrokeStyle,
+        outline_alignment: OutlineAlignment,
+    },
+    Line {
+        path: Path,
+        color: Color,
+        thickness: f32,
+        style: StrokeStyle,
+    },
+    Polyline {
+        path: Path,
+        style: StrokeStyle,
+    },
 }
 
-#[derive(Copy, Clone, Debug)]
-pub struct GeometryVertex {
-    position: Vec2,
-    color: Color,
+/// Coarsest and finest level of detail. Each level halves the tolerance of the previous one, so
+/// shapes stay smooth from 1/16 up to 64 times the size they were tessellated for.
+const MIN_LOD_LEVEL: i32 = -4;
+const MAX_LOD_LEVEL: i32 = 6;
+
+/// The level of detail that keeps a shape drawn `screen_scale` times its size within the
+/// tessellator's tolerance.
+fn lod_level(screen_scale: f32) -> i32 {
+    if screen_scale.is_finite() && screen_scale > 0.0 {
+        (screen_scale.log2().ceil() as i32).clamp(MIN_LOD_LEVEL, MAX_LOD_LEVEL)
+    } else {
+        0
+    }
 }
 
-impl GeometryVertex {
-    pub fn position(&self) -> Vec2 {
-        self.position
+/// Identifies a mesh by everything it was tessellated from. Floats are compared bit for bit and
+/// custom fills by identity.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct MeshKey(Vec<u64>);
+
+impl MeshKey {
+    fn new(tessellation: &Tessellation, tolerance: f32) -> Self {
+        let mut key = Self(Vec::new());
+        key.floats([tolerance]);
+        match tessellation {
+            Tessellation::Shape {
+                path,
+                fill_rule,
+                fill,
+                outline_color,
+                outline_thickness,
+                outline_style,
+                outline_alignment,
+            } => {
+                key.0
+                    .extend([0, *fill_rule as u64, *outline_alignment as u64]);
+                key.path(path);
+                key.fill(fill);
+                key.color(*outline_color);
+                key.floats([*outline_thickness]);
+                key.style(outline_style);
+            }
+            Tessellation::Line {
+                path,
+                color,
+                thickness,
+                style,
+            } => {
+                key.0.push(1);
+                key.path(path);
+                key.color(*color);
+                key.floats([*thickness]);
+                key.style(style);
+            }
+            Tessellation::Polyline { path, style } => {
+                key.0.push(2);
+                key.path(path);
+                key.style(style);
+            }
+        }
+        key
     }
 
-    pub fn color(&self) -> Color {
-        self.color
+    fn floats(&mut self, values: impl IntoIterator<Item = f32>) {
+        self.0
+            .extend(values.into_iter().map(|value| u64::from(value.to_bits())));
     }
-}
 
-pub struct GeometryVertexCtor(Color);
+    fn color(&mut self, color: Color) {
+        self.floats(color.to_array());
+    }
 
-impl FillVertexConstructor<GeometryVertex> for GeometryVertexCtor {
-    fn new_vertex(&mut self, vertex: lyon::tessellation::FillVertex) -> GeometryVertex {
-        let pos = vertex.position();
-        GeometryVertex {
-            position: Vec2::new(pos.x, pos.y),
-            color: self.0,
+    fn path(&mut self, path: &Path) {
+        for event in path.iter_with_attributes() {
+            match event {
+                Event::Begin {
+                    at: (at, attributes),
+                } => {
+                    self.0.push(0);
+                    self.floats([at.x, at.y]);
+                    self.floats(attributes.iter().copied());
+                }
+                Event::Line {
+                    to: (to, attributes),
+                    ..
+                } => {
+                    self.0.push(1);
+                    self.floats([to.x, to.y]);
+                    self.floats(attributes.iter().copied());
+                }
+                Event::Quadratic {
+                    ctrl,
+                    to: (to, attributes),
+                    ..
+                } => {
+                    self.0.push(2);
+                    self.floats([ctrl.x, ctrl.y, to.x, to.y]);
+                    self.floats(attributes.iter().copied());
+                }
+                Event::Cubic {
+                    ctrl1,
+                    ctrl2,
+                    to: (to, attributes),
+                    ..
+                } => {
+                    self.0.push(3);
+                    self.floats([ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y]);
+                    self.floats(attributes.iter().copied());
+                }
+                Event::End { close, .. } => self.0.extend([4, close as u64]),
+            }
         }
     }
-}
 
-impl StrokeVertexConstructor<GeometryVertex> for GeometryVertexCtor {
-    fn new_vertex(&mut self, vertex: lyon::tessellation::StrokeVertex) -> GeometryVertex {
-        let pos = if vertex.side().is_negative() {
-            vertex.position_on_path()
-        } else {
-            vertex.position_on_path() + vertex.normal() * vertex.line_width()
-        };
-        GeometryVertex {
-            position: Vec2::new(pos.x, pos.y),
-            color: self.0,
+    fn fill(&mut self, fill: &Fill) {
+        match fill {
+            Fill::Solid(color) => {
+                self.0.push(0);
+                self.color(*color);
+            }
+            Fill::LinearGradient {
+                start,
+                end,
+                start_color,
+                end_color,
+            } => {
+                self.0.push(1);
+                self.floats([start.x, start.y, end.x, end.y]);
+                self.color(*start_color);
+                self.color(*end_color);
+            }
+            Fill::RadialGradient {
+                center,
+                radius,
+                inner_color,
+                outer_color,
+            } => {
+                self.0.push(2);
+                self.floats([center.x, center.y, *radius]);
+                self.color(*inner_color);
+                self.color(*outer_color);
+            }
+            // The mesh keeps its fill alive, so the address can't be reused while it is cached.
+            Fill::Custom(f) => self
+                .0
+                .extend([3, Arc::as_ptr(f) as *const () as usize as u64]),
         }
     }
-}
-
-pub struct GeometryVertexLineCtor(Color);
 
-impl StrokeVertexConstructor<GeometryVertex> for GeometryVertexLineCtor {
-    fn new_vertex(&mut self, vertex: lyon::tessellation::StrokeVertex) -> GeometryVertex {
-        let pos = vertex.position();
-        GeometryVertex {
-            position: Vec2::new(pos.x, pos.y),
-            color: self.0,
-        }
+    fn style(&mut self, style: &StrokeStyle) {
+        self.0.extend([style.join as u64, style.cap as u64]);
+        self.floats([style.miter_limit, style.dash_offset]);
+        self.0.push(style.dashes.len() as u64);
+        self.floats(style.dashes.iter().copied());
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct CircleShape {
-    pub radius: f32,
-    pub fill_color: Color,
-    pub outline_thickness: f32,
-    pub outline_color: Color,
+/// Tessellated geometry and what it was tessellated from, shared by all shapes with the same
+/// parameters.
+#[derive(Debug)]
+struct Mesh {
     geometry: VertexBuffers<GeometryVertex, u16>,
+    tessellation: Tessellation,
+    tolerance: f32,
+    /// Other levels of detail, tessellated the first time the mesh is drawn at a size that
+    /// needs them.
+    levels: Mutex<HashMap<i32, Arc<VertexBuffers<GeometryVertex, u16>>>>,
 }
 
-impl Default for CircleShape {
-    fn default() -> Self {
-        let geometry = VertexBuffers::new();
-
-        Self {
-            radius: 0.0,
-            fill_color: Color::WHITE,
-            outline_thickness: 0.0,
-            outline_color: Color::WHITE,
-            geometry,
+impl Mesh {
+    /// The geometry to draw the mesh with at `screen_scale` times its size, or `None` if its
+    /// own geometry fits or the level failed to tessellate.
+    fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        let level = lod_level(screen_scale);
+        if level == 0 {
+            return None;
         }
-    }
-}
 
-impl CircleShape {
-    pub fn vertices(&self) -> &[GeometryVertex] {
-        &self.geometry.vertices
-    }
+        let mut levels = self.levels.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(geometry) = levels.get(&level) {
+            return Some(Arc::clone(geometry));
+        }
 
-    pub fn indices(&self) -> &[u16] {
-        &self.geometry.indices
+        let mut geometry = VertexBuffers::new();
+        let tolerance = self.tolerance / 2.0_f32.powi(level);
+        match Tessellator::new(tolerance).tessellate_into(&self.tessellation, &mut geometry) {
+            Ok(()) => {
+                let geometry = Arc::new(geometry);
+                levels.insert(level, Arc::clone(&geometry));
+                Some(geometry)
+            }
+            Err(e) => {
+                log::warn!(
+                    "keeping the shape's own geometry at scale {}: {}",
+                    screen_scale,
+                    e
+                );
+                None
+            }
+        }
     }
 }
 
-impl Geometry for CircleShape {
-    fn update(&mut self, tessellator: &mut Tessellator) {
-        let mut builder = Path::builder();
-        builder.add_circle(
-            point(self.radius, self.radius),
-            self.radius,
-            Winding::Positive,
-        );
-        let path = builder.build();
+/// A shape's mesh. Cloning a shape shares its mesh instead of copying the vertices.
+#[derive(Debug, Default, Clone)]
+struct SharedGeometry(Option<Arc<Mesh>>);
 
-        self.geometry.vertices.drain(..);
-        self.geometry.indices.drain(..);
+impl SharedGeometry {
+    fn vertices(&self) -> &[GeometryVertex] {
+        self.0.as_ref().map_or(&[], |mesh| &mesh.geometry.vertices)
+    }
 
-        tessellator.tesselate(
-            &path,
-            self.fill_color,
-            self.outline_color,
-            self.outline_thickness,
-            &mut self.geometry,
-        );
+    fn indices(&self) -> &[u16] {
+        self.0.as_ref().map_or(&[], |mesh| &mesh.geometry.indices)
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct LineShape {
-    pub length: f32,
-    pub angle: f32,
-    pub outline_thickness: f32,
-    pub outline_color: Color,
-    geometry: VertexBuffers<GeometryVertex, u16>,
-}
+    fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.0.as_ref()?.lod(screen_scale)
+    }
 
-impl Default for LineShape {
-    fn default() -> Self {
-        let geometry = VertexBuffers::new();
+    fn update(
+        &mut self,
+        tessellator: &mut Tessellator,
+        tessellation: Tessellation,
+    ) -> Result<(), GeometryError> {
+        self.0 = None;
+        self.0 = Some(tessellator.mesh(tessellation)?);
+        Ok(())
+    }
 
-        Self {
-            length: 0.0,
-            angle: 0.0,
-            outline_thickness: 0.0,
-            outline_color: Color::WHITE,
-            geometry,
+    /// Passes on the result of checking the shape's parameters, dropping its mesh if they are
+    /// invalid so nothing stale is drawn.
+    fn validate(&mut self, check: Result<(), GeometryError>) -> Result<(), GeometryError> {
+        if check.is_err() {
+            self.0 = None;
         }
+        check
     }
-}
 
-impl LineShape {
-    pub fn vertices(&self) -> &[GeometryVertex] {
-        &self.geometry.vertices
+    fn clear(&mut self) {
+        self.0 = None;
     }
+}
 
-    pub fn indices(&self) -> &[u16] {
-        &self.geometry.indices
+/// Checks that every value of a shape's `parameter` is finite.
+fn check_finite(
+    shape: &'static str,
+    parameter: &str,
+    values: impl IntoIterator<Item = f32>,
+) -> Result<(), GeometryError> {
+    if values.into_iter().all(f32::is_finite) {
+        Ok(())
+    } else {
+        Err(GeometryError::InvalidShape {
+            shape,
+            reason: format!("{} is not finite", parameter),
+        })
     }
 }
 
-impl Geometry for LineShape {
-    fn update(&mut self, tessellator: &mut Tessellator) {
-        let from = point(0.0, 0.0);
-
-        // Position on circumference = (x + r*cos(a), y + r*sin(a))
-        // Where (x, y) is the center of the circle.
-        let a = (-self.angle).to_radians() + 90.0_f32.to_radians();
-        let to = point(
-            from.x + self.length * a.cos(),
-            from.y + self.length * a.sin(),
-        );
-
-        let mut builder = Path::builder();
-        let line = LineSegment { from, to };
-        builder.add_line_segment(&line);
-        let path = builder.build();
-
-        self.geometry.vertices.drain(..);
-        self.geometry.indices.drain(..);
-
-        tessellator.tesselate_line(
-            &path,
-            self.outline_color,
-            self.outline_thickness,
-            &mut self.geometry,
-        );
+/// Checks that every value of a shape's `parameter` is finite and not negative, e.g. for radii.
+fn check_non_negative(
+    shape: &'static str,
+    parameter: &str,
+    values: impl IntoIterator<Item = f32> + Clone,
+) -> Result<(), GeometryError> {
+    check_finite(shape, parameter, values.clone())?;
+    if values.into_iter().all(|value| value >= 0.0) {
+        Ok(())
+    } else {
+        Err(GeometryError::InvalidShape {
+            shape,
+            reason: format!("{} is negative", parameter),
+        })
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct PolygonShape {
-    pub radius: f32,
-    pub point_count: u32,
-    pub fill_color: Color,
-    pub outline_thickness: f32,
-    pub outline_color: Color,
-    geometry: VertexBuffers<GeometryVertex, u16>,
+/// Checks that an outline's thickness and style are finite.
+fn check_outline(
+    shape: &'static str,
+    thickness: f32,
+    style: &StrokeStyle,
+) -> Result<(), GeometryError> {
+    check_finite(shape, "outline_thickness", [thickness])?;
+    check_finite(
+        shape,
+        "outline_style",
+        [style.miter_limit, style.dash_offset]
+            .into_iter()
+            .chain(style.dashes.iter().copied()),
+    )
 }
 
-impl Default for PolygonShape {
-    fn default() -> Self {
-        let geometry = VertexBuffers::new();
+/// Copies `path` with one attribute per endpoint that is positive if the positive side of its
+/// subpath is the inside, i.e. if the subpath runs counter-clockwise.
+fn with_orientation(path: &Path) -> Path {
+    let mut builder = Path::builder_with_attributes(1);
+    let mut subpath = Vec::new();
+    for event in path.iter() {
+        subpath.push(event);
+        if !matches!(event, PathEvent::End { .. }) {
+            continue;
+        }
 
-        Self {
-            radius: 0.0,
-            point_count: 3,
-            fill_color: Color::WHITE,
-            outline_thickness: 0.0,
-            outline_color: Color::WHITE,
-            geometry,
+        // Twice the signed area of the polygon through all points, which has the same sign
+        // as the curved subpath unless it is degenerate.
+        let points = subpath.iter().flat_map(|event| match *event {
+            PathEvent::Begin { at } => vec![at],
+            PathEvent::Line { to, .. } => vec![to],
+            PathEvent::Quadratic { ctrl, to, .. } => vec![ctrl, to],
+            PathEvent::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => vec![ctrl1, ctrl2, to],
+            PathEvent::End { .. } => vec![],
+        });
+        let points = points.collect::<Vec<_>>();
+        let area: f32 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum();
+        let orientation = [if area < 0.0 { -1.0 } else { 1.0 }];
+
+        for event in subpath.drain(..) {
+            match event {
+                PathEvent::Begin { at } => {
+                    builder.begin(at, &orientation);
+                }
+                PathEvent::Line { to, .. } => {
+                    builder.line_to(to, &orientation);
+                }
+                PathEvent::Quadratic { ctrl, to, .. } => {
+                    builder.quadratic_bezier_to(ctrl, to, &orientation);
+                }
+                PathEvent::Cubic {
+                    ctrl1, ctrl2, to, ..
+                } => {
+                    builder.cubic_bezier_to(ctrl1, ctrl2, to, &orientation);
+                }
+                PathEvent::End { close, .. } => builder.end(close),
+            }
         }
     }
+
+    builder.build()
 }
 
-impl PolygonShape {
-    pub fn vertices(&self) -> &[GeometryVertex] {
-        &self.geometry.vertices
+/// Splits `path` into the dashes of `style`, or returns `None` if it has no dashes. Each subpath
+/// starts with the first dash, shifted by the dash offset. Attributes are interpolated along the
+/// dashes.
+fn dash(path: &Path, style: &StrokeStyle, tolerance: f32) -> Option<Path> {
+    // Like SVG, an odd number of lengths is repeated to get an even one.
+    let mut pattern = style.dashes.clone();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_within(..);
     }
 
-    pub fn indices(&self) -> &[u16] {
-        &self.geometry.indices
+    let period: f32 = pattern.iter().sum();
+    if pattern.is_empty() || pattern.iter().any(|length| *length < 0.0) || period <= 0.0 {
+        return None;
     }
-}
 
-impl Geometry for PolygonShape {
-    fn update(&mut self, tessellator: &mut Tessellator) {
-        if self.point_count >= 3 {
-            let points = (0..self.point_count)
-                .map(|i| {
-                    // Position on circumference = (x + r*cos(a), y + r*sin(a))
-                    // Where (x, y) is the center of the circle.
-                    let r = self.radius;
-                    let a = i as f32 / self.point_count as f32 * 360.0_f32.to_radians()
-                        + 90.0_f32.to_radians();
-                    point(r + r * a.cos(), r + r * a.sin())
-                })
-                .collect::<Vec<_>>();
+    let num_attributes = path.num_attributes();
+    let mut dashes = Path::builder_with_attributes(num_attributes);
+    let mut subpath = Path::builder_with_attributes(num_attributes);
+    for event in path.iter_with_attributes() {
+        match event {
+            Event::Begin {
+                at: (at, attributes),
+            } => {
+                subpath.begin(at, attributes);
+                continue;
+            }
+            Event::Line {
+                to: (to, attributes),
+                ..
+            } => {
+                subpath.line_to(to, attributes);
+                continue;
+            }
+            Event::Quadratic {
+                ctrl,
+                to: (to, attributes),
+                ..
+            } => {
+                subpath.quadratic_bezier_to(ctrl, to, attributes);
+                continue;
+            }
+            Event::Cubic {
+                ctrl1,
+                ctrl2,
+                to: (to, attributes),
+                ..
+            } => {
+                subpath.cubic_bezier_to(ctrl1, ctrl2, to, attributes);
+                continue;
+            }
+            Event::End { close, .. } => subpath.end(close),
+        }
 
-            let mut builder = Path::builder();
-            let polygon = Polygon {
-                points: &points,
-                closed: true,
-            };
-            builder.add_polygon(polygon);
-            let path = builder.build();
-
-            self.geometry.vertices.drain(..);
-            self.geometry.indices.drain(..);
-
-            tessellator.tesselate(
-                &path,
-                self.fill_color,
-                self.outline_color,
-                self.outline_thickness,
-                &mut self.geometry,
-            );
+        let subpath =
+            std::mem::replace(&mut subpath, Path::builder_with_attributes(num_attributes)).build();
+        let measurements = PathMeasurements::from_path(&subpath, tolerance);
+        let mut sampler =
+            measurements.create_sampler_with_attributes(&subpath, &subpath, SampleType::Distance);
+        let length = sampler.length();
+        if length <= 0.0 {
+            continue;
+        }
+
+        let mut distance = -style.dash_offset.rem_euclid(period);
+        for (i, dash_length) in pattern.iter().cycle().enumerate() {
+            if distance >= length {
+                break;
+            }
+            if i % 2 == 0 {
+                sampler.split_range(distance..distance + dash_length, &mut dashes);
+            }
+            distance += dash_length;
         }
     }
+
+    Some(dashes.build())
 }
 
-#[derive(Debug, Clone)]
-pub struct RectangleShape {
-    pub size: Vec2,
-    pub fill_color: Color,
-    pub outline_thickness: f32,
-    pub outline_color: Color,
-    geometry: VertexBuffers<GeometryVertex, u16>,
+/// A color in linear space, with straight (not premultiplied) alpha.
+///
+/// The frame is sRGB encoded by default, so linear channels are gamma corrected on their way to
+/// the screen. Colors picked in design tools or written as hex are sRGB encoded and have to be
+/// converted with [`Color::from_srgb`], [`Color::from_hex`] or [`Color::to_linear`] to appear
+/// as picked.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
 }
 
-impl Default for RectangleShape {
+impl Default for Color {
     fn default() -> Self {
-        let geometry = VertexBuffers::new();
-
-        Self {
-            size: Vec2::new(0.0, 0.0),
-            fill_color: Color::WHITE,
-            outline_thickness: 0.0,
-            outline_color: Color::WHITE,
-            geometry,
-        }
+        Self::BLACK
     }
 }
 
-impl RectangleShape {
-    pub fn vertices(&self) -> &[GeometryVertex] {
-        &self.geometry.vertices
+impl Color {
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+    /// `#808080`, half way between black and white on screen.
+    pub const GRAY: Self = Self::new(0.21586, 0.21586, 0.21586, 1.0);
+    pub const RED: Self = Self::new(1.0, 0.0, 0.0, 1.0);
+    pub const GREEN: Self = Self::new(0.0, 1.0, 0.0, 1.0);
+    pub const BLUE: Self = Self::new(0.0, 0.0, 1.0, 1.0);
+    pub const YELLOW: Self = Self::new(1.0, 1.0, 0.0, 1.0);
+    pub const CYAN: Self = Self::new(0.0, 1.0, 1.0, 1.0);
+    pub const MAGENTA: Self = Self::new(1.0, 0.0, 1.0, 1.0);
+    /// `#ffa500`.
+    pub const ORANGE: Self = Self::new(1.0, 0.37626, 0.0, 1.0);
+    /// `#800080`.
+    pub const PURPLE: Self = Self::new(0.21586, 0.0, 0.21586, 1.0);
+
+    /// A color from linear channels.
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
     }
 
-    pub fn indices(&self) -> &[u16] {
-        &self.geometry.indices
+    /// A color from sRGB encoded channels, as picked in design tools. Alpha is linear.
+    pub fn from_srgb(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a)
     }
-}
 
-impl Geometry for RectangleShape {
-    fn update(&mut self, tessellator: &mut Tessellator) {
-        let rect = Box2D::new(point(0.0, 0.0), point(self.size.x, self.size.y));
-        let mut builder = Path::builder();
+    /// Parses an sRGB color written as `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`, with or without
+    /// the `#`.
+    pub fn from_hex(hex: &str) -> Result<Self, ParseColorError> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        let digits = hex
+            .chars()
+            .map(|c| {
+                c.to_digit(16)
+                    .map(|digit| digit as u8)
+                    .ok_or(ParseColorError::InvalidDigit(c))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let channels = match digits.len() {
+            3 | 4 => digits.iter().map(|digit| digit * 17).collect::<Vec<_>>(),
+            6 | 8 => digits
+                .chunks_exact(2)
+                .map(|pair| pair[0] * 16 + pair[1])
+                .collect(),
+            len => return Err(ParseColorError::InvalidLength(len)),
+        };
+        let channel = |i: usize| channels.get(i).map_or(1.0, |&c| c as f32 / 255.0);
+
+        Ok(Self::from_srgb(
+            channel(0),
+            channel(1),
+            channel(2),
+            channel(3),
+        ))
+    }
+
+    /// A color from sRGB hue in degrees, saturation and value, each of the latter in `0..=1`.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32, a: f32) -> Self {
+        let chroma = value * saturation;
+        let [r, g, b] = hue_to_rgb(hue, chroma).map(|c| c + value - chroma);
+        Self::from_srgb(r, g, b, a)
+    }
+
+    /// A color from sRGB hue in degrees, saturation and lightness, each of the latter in
+    /// `0..=1`.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32, a: f32) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let [r, g, b] = hue_to_rgb(hue, chroma).map(|c| c + lightness - chroma / 2.0);
+        Self::from_srgb(r, g, b, a)
+    }
+
+    /// The color's sRGB hue in degrees, saturation and value.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.to_srgb().hue();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    /// The color's sRGB hue in degrees, saturation and lightness.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.to_srgb().hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max > min {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        } else {
+            0.0
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// The color with its channels sRGB encoded, e.g. to show it in a color picker.
+    pub fn to_srgb(&self) -> Self {
+        let encode = |c: f32| {
+            if c <= 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        Self::new(encode(self.r), encode(self.g), encode(self.b), self.a)
+    }
+
+    /// Takes the channels to be sRGB encoded and converts them to linear, for colors created
+    /// with [`Color::new`] from picked values.
+    pub fn to_linear(&self) -> Self {
+        Self::from_srgb(self.r, self.g, self.b, self.a)
+    }
+
+    /// Hue in degrees and the largest and smallest channel of sRGB encoded channels.
+    fn hue(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+        let hue = if delta <= 0.0 {
+            0.0
+        } else if max == self.r {
+            60.0 * ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            60.0 * ((self.b - self.r) / delta + 2.0)
+        } else {
+            60.0 * ((self.r - self.g) / delta + 4.0)
+        };
+        (hue, max, min)
+    }
+
+    /// Linearly interpolates every channel, returning `self` at `t = 0` and `other` at `t = 1`.
+    pub fn lerp(&self, other: Color, t: f32) -> Self {
+        Self::new(
+            self.r + (other.r - self.r) * t,
+            self.g + (other.g - self.g) * t,
+            self.b + (other.b - self.b) * t,
+            self.a + (other.a - self.a) * t,
+        )
+    }
+
+    /// Interpolates between the sRGB encoded channels, which looks more even than [`Color::lerp`]
+    /// between light and dark colors and matches how design tools blend.
+    pub fn mix(&self, other: Color, t: f32) -> Self {
+        self.to_srgb().lerp(other.to_srgb(), t).to_linear()
+    }
+
+    pub const fn to_array(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    /// Returns the color with its RGB channels multiplied by alpha, which is the form the
+    /// renderer blends in.
+    pub fn premultiplied(&self) -> Self {
+        Self::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// The RGB channels of a color with `hue` in degrees and `chroma`, before adding the amount all
+/// channels share.
+fn hue_to_rgb(hue: f32, chroma: f32) -> [f32; 3] {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    match sector as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    }
+}
+
+/// How the inside of a shape is colored. Positions are in the shape's local coordinates.
+///
+/// Colors are computed at the vertices of the tessellated shape and interpolated in between, so
+/// shapes with anything but a solid fill are subdivided to follow the fill more closely.
+#[derive(Clone)]
+pub enum Fill {
+    Solid(Color),
+    /// Blends from `start_color` at `start` to `end_color` at `end`, extending both colors
+    /// beyond the ends.
+    LinearGradient {
+        start: Vec2,
+        end: Vec2,
+        start_color: Color,
+        end_color: Color,
+    },
+    /// Blends from `inner_color` at `center` to `outer_color` at `radius` and beyond.
+    RadialGradient {
+        center: Vec2,
+        radius: f32,
+        inner_color: Color,
+        outer_color: Color,
+    },
+    /// Computes the color at each position, e.g. for patterns.
+    Custom(Arc<dyn Fn(Vec2) -> Color + Send + Sync>),
+}
+
+impl Fill {
+    /// Number of times each triangle of a custom fill is split in four.
+    const CUSTOM_SUBDIVISIONS: u32 = 3;
+    /// Each level quadruples the triangle count, and every shape in a frame shares one index
+    /// buffer.
+    const MAX_SUBDIVISIONS: u32 = 3;
+    /// Gradients are subdivided into about this many steps along their length, up to
+    /// [`Self::MAX_SUBDIVISIONS`].
+    const GRADIENT_STEPS: f32 = 4.0;
+
+    pub fn color_at(&self, position: Vec2) -> Color {
+        match self {
+            Fill::Solid(color) => *color,
+            Fill::LinearGradient {
+                start,
+                end,
+                start_color,
+                end_color,
+            } => {
+                let axis = *end - *start;
+                let t = (position - *start).dot(axis) / axis.length_squared().max(f32::EPSILON);
+                start_color.lerp(*end_color, t.clamp(0.0, 1.0))
+            }
+            Fill::RadialGradient {
+                center,
+                radius,
+                inner_color,
+                outer_color,
+            } => {
+                let t = position.distance(*center) / radius.max(f32::EPSILON);
+                inner_color.lerp(*outer_color, t.clamp(0.0, 1.0))
+            }
+            Fill::Custom(color_at) => color_at(position),
+        }
+    }
+
+    /// Sets the alpha of every color of the fill, returning whether any changed. Custom fills
+    /// compute their own alpha and are left as they are.
+    pub fn set_alpha(&mut self, alpha: f32) -> bool {
+        let (first, second) = match self {
+            Fill::Solid(color) => (Some(color), None),
+            Fill::LinearGradient {
+                start_color,
+                end_color,
+                ..
+            } => (Some(start_color), Some(end_color)),
+            Fill::RadialGradient {
+                inner_color,
+                outer_color,
+                ..
+            } => (Some(inner_color), Some(outer_color)),
+            Fill::Custom(_) => (None, None),
+        };
+
+        first
+            .into_iter()
+            .chain(second)
+            .fold(false, |changed, color| {
+                let color_changed = color.a != alpha;
+                color.a = alpha;
+                changed | color_changed
+            })
+    }
+
+    /// How many times to split each triangle in four so that no edge longer than
+    /// `max_edge_length` remains unsplit where the color varies.
+    fn subdivisions(&self, max_edge_length: f32) -> u32 {
+        let step = match self {
+            Fill::Solid(_) => return 0,
+            Fill::LinearGradient { start, end, .. } => start.distance(*end) / Self::GRADIENT_STEPS,
+            Fill::RadialGradient { radius, .. } => radius / Self::GRADIENT_STEPS,
+            Fill::Custom(_) => return Self::CUSTOM_SUBDIVISIONS,
+        };
+
+        if step <= 0.0 {
+            return 0;
+        }
+
+        (max_edge_length / step)
+            .log2()
+            .ceil()
+            .clamp(0.0, Self::MAX_SUBDIVISIONS as f32) as u32
+    }
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Fill::Solid(Color::WHITE)
+    }
+}
+
+impl From<Color> for Fill {
+    fn from(color: Color) -> Self {
+        Fill::Solid(color)
+    }
+}
+
+impl fmt::Debug for Fill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fill::Solid(color) => f.debug_tuple("Solid").field(color).finish(),
+            Fill::LinearGradient {
+                start,
+                end,
+                start_color,
+                end_color,
+            } => f
+                .debug_struct("LinearGradient")
+                .field("start", start)
+                .field("end", end)
+                .field("start_color", start_color)
+                .field("end_color", end_color)
+                .finish(),
+            Fill::RadialGradient {
+                center,
+                radius,
+                inner_color,
+                outer_color,
+            } => f
+                .debug_struct("RadialGradient")
+                .field("center", center)
+                .field("radius", radius)
+                .field("inner_color", inner_color)
+                .field("outer_color", outer_color)
+                .finish(),
+            Fill::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Splits the triangles of `geometry` from `first_index` on until they are small enough for
+/// `fill` to be interpolated between their vertices. Edges are split at their midpoint, shared
+/// with the neighbouring triangle, so no cracks appear.
+fn subdivide_fill(
+    geometry: &mut VertexBuffers<GeometryVertex, u16>,
+    first_index: usize,
+    fill: &Fill,
+) {
+    let max_edge_length = geometry.indices[first_index..]
+        .chunks_exact(3)
+        .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+        .map(|(a, b)| {
+            let a = geometry.vertices[a as usize].position;
+            let b = geometry.vertices[b as usize].position;
+            a.distance(b)
+        })
+        .fold(0.0, f32::max);
+
+    for _ in 0..fill.subdivisions(max_edge_length) {
+        // Every level adds at most one vertex per edge; stop before indices overflow.
+        let triangles = (geometry.indices.len() - first_index) / 3;
+        if geometry.vertices.len() + triangles * 3 > u16::MAX as usize {
+            break;
+        }
+
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |vertices: &mut Vec<GeometryVertex>, a: u16, b: u16| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let position =
+                    (vertices[a as usize].position + vertices[b as usize].position) / 2.0;
+                vertices.push(GeometryVertex {
+                    position,
+                    color: fill.color_at(position),
+                    uv: Vec2::ZERO,
+                });
+                (vertices.len() - 1) as u16
+            })
+        };
+
+        let triangles = geometry.indices.split_off(first_index);
+        for t in triangles.chunks_exact(3) {
+            let (a, b, c) = (t[0], t[1], t[2]);
+            let ab = midpoint(&mut geometry.vertices, a, b);
+            let bc = midpoint(&mut geometry.vertices, b, c);
+            let ca = midpoint(&mut geometry.vertices, c, a);
+            geometry
+                .indices
+                .extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+        }
+    }
+}
+
+/// The shape used where two segments of an outline meet.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Extends both edges until they meet, falling back to [`LineJoin::Bevel`] past the miter
+    /// limit.
+    #[default]
+    Miter,
+    /// Like [`LineJoin::Miter`] but clips the corner at the miter limit.
+    MiterClip,
+    Round,
+    Bevel,
+}
+
+impl From<LineJoin> for lyon::tessellation::LineJoin {
+    fn from(join: LineJoin) -> Self {
+        match join {
+            LineJoin::Miter => lyon::tessellation::LineJoin::Miter,
+            LineJoin::MiterClip => lyon::tessellation::LineJoin::MiterClip,
+            LineJoin::Round => lyon::tessellation::LineJoin::Round,
+            LineJoin::Bevel => lyon::tessellation::LineJoin::Bevel,
+        }
+    }
+}
+
+/// The shape at the open ends of lines and dashes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends exactly at the end of the line.
+    #[default]
+    Butt,
+    /// Extends past the end by half the line's thickness.
+    Square,
+    Round,
+}
+
+impl From<LineCap> for lyon::tessellation::LineCap {
+    fn from(cap: LineCap) -> Self {
+        match cap {
+            LineCap::Butt => lyon::tessellation::LineCap::Butt,
+            LineCap::Square => lyon::tessellation::LineCap::Square,
+            LineCap::Round => lyon::tessellation::LineCap::Round,
+        }
+    }
+}
+
+/// How lines and outlines are drawn, beyond their thickness and color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// How far a miter join may extend, as a multiple of half the thickness. At least 1.
+    pub miter_limit: f32,
+    /// Alternating lengths of dashes and gaps, e.g. `[10.0, 5.0]`. Empty for a solid line.
+    pub dashes: Vec<f32>,
+    /// How far into the dash pattern each line or outline starts.
+    pub dash_offset: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: StrokeOptions::DEFAULT_MITER_LIMIT,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+}
+
+/// Where a shape's outline is drawn relative to its edge.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum OutlineAlignment {
+    /// Within the shape, so the outline doesn't change its size.
+    #[default]
+    Inside,
+    /// Half inside and half outside, like SVG strokes.
+    Centered,
+    /// Around the shape, growing it by the outline's thickness on every side.
+    Outside,
+}
+
+/// Decides which parts of a path with overlapping or nested subpaths are inside it.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum FillRule {
+    /// A point is inside if the subpaths around it wind more often one way than the other, so
+    /// holes must wind opposite to their outline.
+    #[default]
+    NonZero,
+    /// A point is inside if it is surrounded by an odd number of subpaths, so nested subpaths
+    /// are holes regardless of winding.
+    EvenOdd,
+}
+
+impl From<FillRule> for lyon::tessellation::FillRule {
+    fn from(fill_rule: FillRule) -> Self {
+        match fill_rule {
+            FillRule::NonZero => lyon::tessellation::FillRule::NonZero,
+            FillRule::EvenOdd => lyon::tessellation::FillRule::EvenOdd,
+        }
+    }
+}
+
+impl From<FillRule> for i_overlay::core::fill_rule::FillRule {
+    fn from(fill_rule: FillRule) -> Self {
+        match fill_rule {
+            FillRule::NonZero => i_overlay::core::fill_rule::FillRule::NonZero,
+            FillRule::EvenOdd => i_overlay::core::fill_rule::FillRule::EvenOdd,
+        }
+    }
+}
+
+/// How a shape's colors are combined with what has already been drawn beneath it.
+///
+/// Shapes using anything other than [`BlendMode::Alpha`], or with any translucent color, are
+/// drawn after all opaque shapes, sorted back-to-front by [`crate::components::Transform::z_index`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Standard "over" compositing.
+    #[default]
+    Alpha,
+    /// Adds the shape's color to the destination; useful for glows.
+    Additive,
+    /// Multiplies the destination by the shape's color; useful for shadows and tints.
+    Multiply,
+}
+
+pub trait Geometry {
+    /// Tessellates the shape from its current parameters. On failure the shape is left without
+    /// geometry so nothing stale is drawn.
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError>;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct GeometryVertex {
+    position: Vec2,
+    color: Color,
+    uv: Vec2,
+}
+
+impl GeometryVertex {
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Texture coordinates, with (0, 0) at the top left of the texture.
+    pub fn uv(&self) -> Vec2 {
+        self.uv
+    }
+}
+
+/// Colors fill vertices by their position.
+struct FillVertexCtor<'a>(&'a Fill);
+
+impl FillVertexConstructor<GeometryVertex> for FillVertexCtor<'_> {
+    fn new_vertex(&mut self, vertex: lyon::tessellation::FillVertex) -> GeometryVertex {
+        let pos = vertex.position();
+        let position = Vec2::new(pos.x, pos.y);
+        GeometryVertex {
+            position,
+            color: self.0.color_at(position),
+            uv: Vec2::ZERO,
+        }
+    }
+}
+
+pub struct GeometryVertexCtor(Color, OutlineAlignment);
+
+impl FillVertexConstructor<GeometryVertex> for GeometryVertexCtor {
+    fn new_vertex(&mut self, vertex: lyon::tessellation::FillVertex) -> GeometryVertex {
+        let pos = vertex.position();
+        GeometryVertex {
+            position: Vec2::new(pos.x, pos.y),
+            color: self.0,
+            uv: Vec2::ZERO,
+        }
+    }
+}
+
+/// Aligns outline vertices using the orientation stored by [`with_orientation`].
+impl StrokeVertexConstructor<GeometryVertex> for GeometryVertexCtor {
+    fn new_vertex(&mut self, mut vertex: lyon::tessellation::StrokeVertex) -> GeometryVertex {
+        let counter_clockwise = vertex.interpolated_attributes().first() >= Some(&0.0);
+        let inside = vertex.side().is_positive() == counter_clockwise;
+        // How many half widths the vertex lies from the edge.
+        let offset = match (self.1, inside) {
+            (OutlineAlignment::Centered, _) => 1.0,
+            (OutlineAlignment::Inside, true) | (OutlineAlignment::Outside, false) => 2.0,
+            (OutlineAlignment::Inside, false) | (OutlineAlignment::Outside, true) => 0.0,
+        };
+        let pos = vertex.position_on_path() + vertex.normal() * vertex.line_width() / 2.0 * offset;
+        GeometryVertex {
+            position: Vec2::new(pos.x, pos.y),
+            color: self.0,
+            uv: Vec2::ZERO,
+        }
+    }
+}
+
+/// Colors stroke vertices with the color stored after the thickness in the path's attributes.
+struct PolylineVertexCtor;
+
+impl StrokeVertexConstructor<GeometryVertex> for PolylineVertexCtor {
+    fn new_vertex(&mut self, mut vertex: lyon::tessellation::StrokeVertex) -> GeometryVertex {
+        let pos = vertex.position();
+        let color = match vertex.interpolated_attributes() {
+            [_, r, g, b, a] => Color::new(*r, *g, *b, *a),
+            _ => Color::WHITE,
+        };
+        GeometryVertex {
+            position: Vec2::new(pos.x, pos.y),
+            color,
+            uv: Vec2::ZERO,
+        }
+    }
+}
+
+pub struct GeometryVertexLineCtor(Color);
+
+impl StrokeVertexConstructor<GeometryVertex> for GeometryVertexLineCtor {
+    fn new_vertex(&mut self, vertex: lyon::tessellation::StrokeVertex) -> GeometryVertex {
+        let pos = vertex.position();
+        GeometryVertex {
+            position: Vec2::new(pos.x, pos.y),
+            color: self.0,
+            uv: Vec2::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CircleShape {
+    pub radius: f32,
+    pub fill: Fill,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub outline_alignment: OutlineAlignment,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for CircleShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            radius: 0.0,
+            fill: Fill::default(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            outline_alignment: OutlineAlignment::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl CircleShape {
+    const OUTLINE_SEGMENTS: u32 = 32;
+
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// Polygonal approximation of the circle in local coordinates.
+    pub fn outline(&self) -> Vec<Vec2> {
+        let r = self.radius;
+        (0..Self::OUTLINE_SEGMENTS)
+            .map(|i| {
+                let a = i as f32 / Self::OUTLINE_SEGMENTS as f32 * 360.0_f32.to_radians();
+                Vec2::new(r + r * a.cos(), r + r * a.sin())
+            })
+            .collect()
+    }
+}
+
+impl Geometry for CircleShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_non_negative("CircleShape", "radius", [self.radius]).and_then(|()| {
+                check_outline("CircleShape", self.outline_thickness, &self.outline_style)
+            }),
+        )?;
+
+        let mut builder = Path::builder();
+        builder.add_circle(
+            point(self.radius, self.radius),
+            self.radius,
+            Winding::Positive,
+        );
+        let path = builder.build();
+
+        let tessellation = Tessellation::Shape {
+            path,
+            fill_rule: FillRule::NonZero,
+            fill: self.fill.clone(),
+            outline_color: self.outline_color,
+            outline_thickness: self.outline_thickness,
+            outline_style: self.outline_style.clone(),
+            outline_alignment: self.outline_alignment,
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LineShape {
+    pub length: f32,
+    pub angle: f32,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for LineShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            length: 0.0,
+            angle: 0.0,
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl LineShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+}
+
+impl Geometry for LineShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_finite("LineShape", "length", [self.length])
+                .and_then(|()| check_finite("LineShape", "angle", [self.angle]))
+                .and_then(|()| {
+                    check_outline("LineShape", self.outline_thickness, &self.outline_style)
+                }),
+        )?;
+
+        let from = point(0.0, 0.0);
+
+        // Position on circumference = (x + r*cos(a), y + r*sin(a))
+        // Where (x, y) is the center of the circle.
+        let a = (-self.angle).to_radians() + 90.0_f32.to_radians();
+        let to = point(
+            from.x + self.length * a.cos(),
+            from.y + self.length * a.sin(),
+        );
+
+        let mut builder = Path::builder();
+        let line = LineSegment { from, to };
+        builder.add_line_segment(&line);
+        let path = builder.build();
+
+        let tessellation = Tessellation::Line {
+            path,
+            color: self.outline_color,
+            thickness: self.outline_thickness,
+            style: self.outline_style.clone(),
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PolygonShape {
+    pub radius: f32,
+    pub point_count: u32,
+    pub fill: Fill,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub outline_alignment: OutlineAlignment,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for PolygonShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            radius: 0.0,
+            point_count: 3,
+            fill: Fill::default(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            outline_alignment: OutlineAlignment::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl PolygonShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// The polygon's corners in local coordinates.
+    pub fn outline(&self) -> Vec<Vec2> {
+        (0..self.point_count)
+            .map(|i| {
+                // Position on circumference = (x + r*cos(a), y + r*sin(a))
+                // Where (x, y) is the center of the circle.
+                let r = self.radius;
+                let a = i as f32 / self.point_count as f32 * 360.0_f32.to_radians()
+                    + 90.0_f32.to_radians();
+                Vec2::new(r + r * a.cos(), r + r * a.sin())
+            })
+            .collect()
+    }
+}
+
+impl Geometry for PolygonShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        if self.point_count < 3 {
+            let error = GeometryError::InvalidShape {
+                shape: "PolygonShape",
+                reason: format!("point_count is {}, needs at least 3", self.point_count),
+            };
+            return self.geometry.validate(Err(error));
+        }
+
+        self.geometry.validate(
+            check_finite("PolygonShape", "radius", [self.radius]).and_then(|()| {
+                check_outline("PolygonShape", self.outline_thickness, &self.outline_style)
+            }),
+        )?;
+
+        let points = self
+            .outline()
+            .into_iter()
+            .map(|p| point(p.x, p.y))
+            .collect::<Vec<_>>();
+
+        let mut builder = Path::builder();
+        let polygon = Polygon {
+            points: &points,
+            closed: true,
+        };
+        builder.add_polygon(polygon);
+        let path = builder.build();
+
+        let tessellation = Tessellation::Shape {
+            path,
+            fill_rule: FillRule::NonZero,
+            fill: self.fill.clone(),
+            outline_color: self.outline_color,
+            outline_thickness: self.outline_thickness,
+            outline_style: self.outline_style.clone(),
+            outline_alignment: self.outline_alignment,
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RectangleShape {
+    pub size: Vec2,
+    pub fill: Fill,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub outline_alignment: OutlineAlignment,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for RectangleShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            size: Vec2::new(0.0, 0.0),
+            fill: Fill::default(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            outline_alignment: OutlineAlignment::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl RectangleShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// The rectangle's corners in local coordinates.
+    pub fn outline(&self) -> Vec<Vec2> {
+        vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(self.size.x, 0.0),
+            Vec2::new(self.size.x, self.size.y),
+            Vec2::new(0.0, self.size.y),
+        ]
+    }
+}
+
+impl Geometry for RectangleShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_finite("RectangleShape", "size", self.size.to_array()).and_then(|()| {
+                check_outline(
+                    "RectangleShape",
+                    self.outline_thickness,
+                    &self.outline_style,
+                )
+            }),
+        )?;
+
+        let rect = Box2D::new(point(0.0, 0.0), point(self.size.x, self.size.y));
+        let mut builder = Path::builder();
         builder.add_rectangle(&rect, Winding::Positive);
         let path = builder.build();
 
+        let tessellation = Tessellation::Shape {
+            path,
+            fill_rule: FillRule::NonZero,
+            fill: self.fill.clone(),
+            outline_color: self.outline_color,
+            outline_thickness: self.outline_thickness,
+            outline_style: self.outline_style.clone(),
+            outline_alignment: self.outline_alignment,
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RoundedRectangleShape {
+    pub size: Vec2,
+    /// Radius of every corner, limited to half the shorter side.
+    pub corner_radius: f32,
+    pub fill: Fill,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub outline_alignment: OutlineAlignment,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for RoundedRectangleShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            size: Vec2::new(0.0, 0.0),
+            corner_radius: 0.0,
+            fill: Fill::default(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            outline_alignment: OutlineAlignment::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl RoundedRectangleShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// Polygonal approximation of the rounded rectangle in local coordinates.
+    pub fn outline(&self) -> Vec<Vec2> {
+        flatten_outline(&self.path()).unwrap_or_default()
+    }
+
+    fn path(&self) -> Path {
+        let rect = Box2D::new(point(0.0, 0.0), point(self.size.x, self.size.y));
+        let radius = self
+            .corner_radius
+            .clamp(0.0, self.size.min_element().max(0.0) / 2.0);
+        let mut builder = Path::builder();
+        builder.add_rounded_rectangle(&rect, &BorderRadii::new(radius), Winding::Positive);
+        builder.build()
+    }
+}
+
+impl Geometry for RoundedRectangleShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_finite("RoundedRectangleShape", "size", self.size.to_array())
+                .and_then(|()| {
+                    check_finite(
+                        "RoundedRectangleShape",
+                        "corner_radius",
+                        [self.corner_radius],
+                    )
+                })
+                .and_then(|()| {
+                    check_outline(
+                        "RoundedRectangleShape",
+                        self.outline_thickness,
+                        &self.outline_style,
+                    )
+                }),
+        )?;
+
+        let path = self.path();
+
+        let tessellation = Tessellation::Shape {
+            path,
+            fill_rule: FillRule::NonZero,
+            fill: self.fill.clone(),
+            outline_color: self.outline_color,
+            outline_thickness: self.outline_thickness,
+            outline_style: self.outline_style.clone(),
+            outline_alignment: self.outline_alignment,
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EllipseShape {
+    /// Horizontal and vertical radius.
+    pub radii: Vec2,
+    pub fill: Fill,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub outline_alignment: OutlineAlignment,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for EllipseShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            radii: Vec2::new(0.0, 0.0),
+            fill: Fill::default(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            outline_alignment: OutlineAlignment::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl EllipseShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// Polygonal approximation of the ellipse in local coordinates.
+    pub fn outline(&self) -> Vec<Vec2> {
+        flatten_outline(&self.path()).unwrap_or_default()
+    }
+
+    fn path(&self) -> Path {
+        let mut builder = Path::builder();
+        builder.add_ellipse(
+            point(self.radii.x, self.radii.y),
+            vector(self.radii.x, self.radii.y),
+            Angle::zero(),
+            Winding::Positive,
+        );
+        builder.build()
+    }
+}
+
+impl Geometry for EllipseShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_finite("EllipseShape", "radii", self.radii.to_array()).and_then(|()| {
+                check_outline("EllipseShape", self.outline_thickness, &self.outline_style)
+            }),
+        )?;
+
+        let path = self.path();
+
+        let tessellation = Tessellation::Shape {
+            path,
+            fill_rule: FillRule::NonZero,
+            fill: self.fill.clone(),
+            outline_color: self.outline_color,
+            outline_thickness: self.outline_thickness,
+            outline_style: self.outline_style.clone(),
+            outline_alignment: self.outline_alignment,
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+/// Adds the part of the circle around `center` from `start_angle` over `sweep_angle` to
+/// `builder`. Angles are in degrees, clockwise from the top like [`LineShape::angle`].
+fn add_arc(
+    builder: &mut WithSvg<BuilderImpl>,
+    center: Vec2,
+    radius: f32,
+    start_angle: f32,
+    sweep_angle: f32,
+) {
+    let a = (90.0 - start_angle).to_radians();
+    let start = center + Vec2::new(a.cos(), a.sin()) * radius;
+    builder.line_to(point(start.x, start.y));
+    builder.arc(
+        point(center.x, center.y),
+        vector(radius, radius),
+        Angle::degrees(-sweep_angle),
+        Angle::zero(),
+    );
+}
+
+/// Part of a circle's circumference, e.g. a cooldown indicator. Like [`LineShape`] it only has
+/// an outline, centered on the arc.
+#[derive(Debug, Clone)]
+pub struct ArcShape {
+    pub radius: f32,
+    /// Where the arc starts in degrees, clockwise from the top of the circle.
+    pub start_angle: f32,
+    /// How far the arc extends clockwise in degrees.
+    pub sweep_angle: f32,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for ArcShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            radius: 0.0,
+            start_angle: 0.0,
+            sweep_angle: 0.0,
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl ArcShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+}
+
+impl Geometry for ArcShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_non_negative("ArcShape", "radius", [self.radius])
+                .and_then(|()| {
+                    check_finite("ArcShape", "angles", [self.start_angle, self.sweep_angle])
+                })
+                .and_then(|()| {
+                    check_outline("ArcShape", self.outline_thickness, &self.outline_style)
+                }),
+        )?;
+
+        let mut builder = Path::svg_builder();
+        add_arc(
+            &mut builder,
+            Vec2::splat(self.radius),
+            self.radius,
+            self.start_angle,
+            self.sweep_angle,
+        );
+        let path = builder.build();
+
+        let tessellation = Tessellation::Line {
+            path,
+            color: self.outline_color,
+            thickness: self.outline_thickness,
+            style: self.outline_style.clone(),
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+/// A pie slice of a circle, e.g. a filling cooldown indicator.
+#[derive(Debug, Clone)]
+pub struct SectorShape {
+    pub radius: f32,
+    /// Where the sector starts in degrees, clockwise from the top of the circle.
+    pub start_angle: f32,
+    /// How far the sector extends clockwise in degrees.
+    pub sweep_angle: f32,
+    pub fill: Fill,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub outline_alignment: OutlineAlignment,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for SectorShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            radius: 0.0,
+            start_angle: 0.0,
+            sweep_angle: 0.0,
+            fill: Fill::default(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            outline_alignment: OutlineAlignment::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl SectorShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// Polygonal approximation of the sector in local coordinates.
+    pub fn outline(&self) -> Vec<Vec2> {
+        flatten_outline(&self.path()).unwrap_or_default()
+    }
+
+    fn path(&self) -> Path {
+        let center = Vec2::splat(self.radius);
+        if self.sweep_angle.abs() >= 360.0 {
+            // A full turn is a circle; the edges to the center would only leave a seam.
+            let mut builder = Path::builder();
+            builder.add_circle(point(center.x, center.y), self.radius, Winding::Positive);
+            return builder.build();
+        }
+
+        let mut builder = Path::svg_builder();
+        builder.move_to(point(center.x, center.y));
+        add_arc(
+            &mut builder,
+            center,
+            self.radius,
+            self.start_angle,
+            self.sweep_angle.clamp(-360.0, 360.0),
+        );
+        builder.close();
+        builder.build()
+    }
+}
+
+impl Geometry for SectorShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_non_negative("SectorShape", "radius", [self.radius])
+                .and_then(|()| {
+                    check_finite(
+                        "SectorShape",
+                        "angles",
+                        [self.start_angle, self.sweep_angle],
+                    )
+                })
+                .and_then(|()| {
+                    check_outline("SectorShape", self.outline_thickness, &self.outline_style)
+                }),
+        )?;
+
+        let path = self.path();
+
+        let tessellation = Tessellation::Shape {
+            path,
+            fill_rule: FillRule::NonZero,
+            fill: self.fill.clone(),
+            outline_color: self.outline_color,
+            outline_thickness: self.outline_thickness,
+            outline_style: self.outline_style.clone(),
+            outline_alignment: self.outline_alignment,
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+/// A circle with a round hole in the middle, e.g. a radar ping. Both edges are outlined.
+#[derive(Debug, Clone)]
+pub struct RingShape {
+    pub radius: f32,
+    /// Radius of the hole, smaller than [`Self::radius`]. A ring with an inner radius of 0 has
+    /// no hole.
+    pub inner_radius: f32,
+    pub fill: Fill,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub outline_alignment: OutlineAlignment,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for RingShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            radius: 0.0,
+            inner_radius: 0.0,
+            fill: Fill::default(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            outline_alignment: OutlineAlignment::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl RingShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// Polygonal approximation of the ring's outer edge in local coordinates; light passes
+    /// through the hole.
+    pub fn outline(&self) -> Vec<Vec2> {
+        flatten_outline(&self.path()).unwrap_or_default()
+    }
+
+    fn path(&self) -> Path {
+        let center = point(self.radius, self.radius);
+        let mut builder = Path::builder();
+        builder.add_circle(center, self.radius, Winding::Positive);
+        if self.inner_radius > 0.0 {
+            // Winding the hole the other way cuts it out with the non-zero fill rule.
+            builder.add_circle(center, self.inner_radius, Winding::Negative);
+        }
+        builder.build()
+    }
+}
+
+impl Geometry for RingShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_non_negative("RingShape", "radii", [self.radius, self.inner_radius])
+                .and_then(|()| {
+                    if self.inner_radius > 0.0 && self.inner_radius >= self.radius {
+                        return Err(GeometryError::InvalidShape {
+                            shape: "RingShape",
+                            reason: format!(
+                                "inner_radius is {}, needs to be smaller than radius {}",
+                                self.inner_radius, self.radius
+                            ),
+                        });
+                    }
+                    Ok(())
+                })
+                .and_then(|()| {
+                    check_outline("RingShape", self.outline_thickness, &self.outline_style)
+                }),
+        )?;
+
+        let path = self.path();
+
+        let tessellation = Tessellation::Shape {
+            path,
+            fill_rule: FillRule::NonZero,
+            fill: self.fill.clone(),
+            outline_color: self.outline_color,
+            outline_thickness: self.outline_thickness,
+            outline_style: self.outline_style.clone(),
+            outline_alignment: self.outline_alignment,
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+/// A line through several points, e.g. a trail behind a bullet, a laser beam or a debug path.
+/// Like [`LineShape`] it only has an outline, centered on the line.
+#[derive(Debug, Clone)]
+pub struct PolylineShape {
+    /// The points in local coordinates; at least two are needed to draw anything.
+    pub points: Vec<Vec2>,
+    /// Whether the last point connects back to the first.
+    pub closed: bool,
+    /// Thickness at each point, blending in between. Points without an entry use
+    /// [`Self::outline_thickness`].
+    pub thicknesses: Vec<f32>,
+    /// Color at each point, blending in between. Points without an entry use
+    /// [`Self::outline_color`].
+    pub colors: Vec<Color>,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for PolylineShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            points: Vec::new(),
+            closed: false,
+            thicknesses: Vec::new(),
+            colors: Vec::new(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl PolylineShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// The thickness and color of every point, which are stored as the path's attributes.
+    fn attributes(&self, i: usize) -> [f32; 5] {
+        let thickness = self
+            .thicknesses
+            .get(i)
+            .copied()
+            .unwrap_or(self.outline_thickness);
+        let color = self.colors.get(i).copied().unwrap_or(self.outline_color);
+        [thickness.max(0.0), color.r, color.g, color.b, color.a]
+    }
+}
+
+impl Geometry for PolylineShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_finite(
+                "PolylineShape",
+                "points",
+                self.points.iter().flat_map(|p| p.to_array()),
+            )
+            .and_then(|()| {
+                check_finite(
+                    "PolylineShape",
+                    "thicknesses",
+                    self.thicknesses.iter().copied(),
+                )
+            })
+            .and_then(|()| {
+                check_finite(
+                    "PolylineShape",
+                    "colors",
+                    self.colors.iter().flat_map(|c| c.to_array()),
+                )
+            })
+            .and_then(|()| {
+                check_outline("PolylineShape", self.outline_thickness, &self.outline_style)
+            }),
+        )?;
+
+        if self.points.len() < 2 {
+            self.geometry.clear();
+            return Ok(());
+        }
+
+        let mut builder = Path::builder_with_attributes(5);
+        for (i, p) in self.points.iter().enumerate() {
+            if i == 0 {
+                builder.begin(point(p.x, p.y), &self.attributes(i));
+            } else {
+                builder.line_to(point(p.x, p.y), &self.attributes(i));
+            }
+        }
+        builder.end(self.closed);
+        let path = builder.build();
+
+        let tessellation = Tessellation::Polyline {
+            path,
+            style: self.outline_style.clone(),
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+/// An axis-aligned rectangle.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Rect {
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+impl Rect {
+    pub const fn new(position: Vec2, size: Vec2) -> Self {
+        Self { position, size }
+    }
+
+    /// Whether `point` lies inside the rectangle, including its top and left edges.
+    pub fn contains(&self, point: Vec2) -> bool {
+        let end = self.position + self.size;
+        point.cmpge(self.position).all() && point.cmplt(end).all()
+    }
+}
+
+/// A textured rectangle. Sprites are always drawn as translucent since their texture may be.
+#[derive(Debug, Clone)]
+pub struct SpriteShape {
+    pub size: Vec2,
+    pub texture: Option<Texture>,
+    /// Region of the texture to draw, in texels from the top left. `None` draws the whole
+    /// texture.
+    pub source: Option<Rect>,
+    /// Multiplied with the texture's colors.
+    pub color: Color,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: VertexBuffers<GeometryVertex, u16>,
+}
+
+impl Default for SpriteShape {
+    fn default() -> Self {
+        let geometry = VertexBuffers::new();
+
+        Self {
+            size: Vec2::new(0.0, 0.0),
+            texture: None,
+            source: None,
+            color: Color::WHITE,
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl SpriteShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        &self.geometry.vertices
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        &self.geometry.indices
+    }
+
+    /// The sprite's corners in local coordinates.
+    pub fn outline(&self) -> Vec<Vec2> {
+        vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(self.size.x, 0.0),
+            Vec2::new(self.size.x, self.size.y),
+            Vec2::new(0.0, self.size.y),
+        ]
+    }
+
+    /// The source region in texture coordinates, with (0, 0) at the top left.
+    fn uv_rect(&self) -> Rect {
+        self.source_uv_rect(self.source)
+    }
+
+    /// Moves texture coordinates `uv` of the sprite's geometry from its own source region to
+    /// `source`, e.g. the current frame of an animation.
+    pub(crate) fn frame_uv(&self, uv: Vec2, source: Rect) -> Vec2 {
+        let from = self.uv_rect();
+        let to = self.source_uv_rect(Some(source));
+        let corner = ((uv - from.position) / from.size).clamp(Vec2::ZERO, Vec2::ONE);
+        // An empty source region has no corners to tell apart.
+        let corner = Vec2::select(corner.is_nan_mask(), Vec2::ZERO, corner);
+        to.position + corner * to.size
+    }
+
+    fn source_uv_rect(&self, source: Option<Rect>) -> Rect {
+        match (&self.texture, source) {
+            (Some(texture), Some(source)) => {
+                let size = texture.size().as_vec2();
+                Rect::new(source.position / size, source.size / size)
+            }
+            _ => Rect::new(Vec2::ZERO, Vec2::ONE),
+        }
+    }
+}
+
+impl Geometry for SpriteShape {
+    fn update(&mut self, _tessellator: &mut Tessellator) -> Result<(), GeometryError> {
         self.geometry.vertices.drain(..);
         self.geometry.indices.drain(..);
+        check_finite("SpriteShape", "size", self.size.to_array())?;
+
+        // Local y points up while texture rows go down, so the top of the sprite samples the
+        // top of the source region.
+        let uv = self.uv_rect();
+        let corners = [
+            (Vec2::new(0.0, 0.0), Vec2::new(0.0, 1.0)),
+            (Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0)),
+            (Vec2::new(1.0, 1.0), Vec2::new(1.0, 0.0)),
+            (Vec2::new(0.0, 1.0), Vec2::new(0.0, 0.0)),
+        ];
+        for (position, corner_uv) in corners {
+            self.geometry.vertices.push(GeometryVertex {
+                position: position * self.size,
+                color: self.color,
+                uv: uv.position + corner_uv * uv.size,
+            });
+        }
+
+        self.geometry.indices.extend_from_slice(&[0, 1, 2, 0, 2, 3]);
+        Ok(())
+    }
+}
+
+/// Builds the outline of a [`PathShape`] from lines, curves and arcs, in local coordinates.
+/// Angles are in degrees like [`crate::components::Transform::rotation`].
+///
+/// Drawing without a [`Self::move_to`] first starts at the end of the previous subpath, and
+/// subpaths that aren't closed are still filled as if they were.
+pub struct PathBuilder(WithSvg<BuilderImpl>);
+
+impl Default for PathBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathBuilder {
+    pub fn new() -> Self {
+        Self(Path::svg_builder())
+    }
+
+    /// Starts a new subpath at `to`.
+    pub fn move_to(&mut self, to: Vec2) -> &mut Self {
+        self.0.move_to(point(to.x, to.y));
+        self
+    }
+
+    pub fn line_to(&mut self, to: Vec2) -> &mut Self {
+        self.0.line_to(point(to.x, to.y));
+        self
+    }
+
+    pub fn quadratic_bezier_to(&mut self, control: Vec2, to: Vec2) -> &mut Self {
+        self.0
+            .quadratic_bezier_to(point(control.x, control.y), point(to.x, to.y));
+        self
+    }
+
+    pub fn cubic_bezier_to(&mut self, control1: Vec2, control2: Vec2, to: Vec2) -> &mut Self {
+        self.0.cubic_bezier_to(
+            point(control1.x, control1.y),
+            point(control2.x, control2.y),
+            point(to.x, to.y),
+        );
+        self
+    }
+
+    /// Sweeps the current position around `center` along an ellipse with the given radii,
+    /// counter-clockwise for positive `sweep_angle`.
+    pub fn arc(
+        &mut self,
+        center: Vec2,
+        radii: Vec2,
+        sweep_angle: f32,
+        x_rotation: f32,
+    ) -> &mut Self {
+        self.0.arc(
+            point(center.x, center.y),
+            vector(radii.x, radii.y),
+            Angle::degrees(sweep_angle),
+            Angle::degrees(x_rotation),
+        );
+        self
+    }
+
+    /// An elliptical arc to `to` like SVG's `A` command: of the four arcs with the given radii
+    /// that connect both points, `large_arc` picks one of more than 180 degrees and `sweep` one
+    /// going counter-clockwise.
+    pub fn arc_to(
+        &mut self,
+        radii: Vec2,
+        x_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: Vec2,
+    ) -> &mut Self {
+        use lyon::path::builder::SvgPathBuilder;
+
+        SvgPathBuilder::arc_to(
+            &mut self.0,
+            vector(radii.x, radii.y),
+            Angle::degrees(x_rotation),
+            ArcFlags { large_arc, sweep },
+            point(to.x, to.y),
+        );
+        self
+    }
+
+    /// Closes the current subpath with a line back to its start.
+    pub fn close(&mut self) -> &mut Self {
+        self.0.close();
+        self
+    }
+
+    /// Adds a circle as a closed subpath of its own.
+    pub fn circle(&mut self, center: Vec2, radius: f32) -> &mut Self {
+        self.move_to(center + Vec2::new(radius, 0.0))
+            .arc(center, Vec2::splat(radius), 360.0, 0.0)
+            .close()
+    }
+
+    /// Adds a rectangle with its bottom left at `position` as a closed subpath of its own.
+    pub fn rectangle(&mut self, position: Vec2, size: Vec2) -> &mut Self {
+        self.move_to(position)
+            .line_to(position + Vec2::new(size.x, 0.0))
+            .line_to(position + size)
+            .line_to(position + Vec2::new(0.0, size.y))
+            .close()
+    }
+
+    pub fn build(self) -> Path {
+        self.0.build()
+    }
+}
+
+/// An arbitrary vector shape, e.g. a star, ship or terrain silhouette, see [`PathBuilder`].
+#[derive(Debug, Clone)]
+pub struct PathShape {
+    pub path: Path,
+    pub fill_rule: FillRule,
+    pub fill: Fill,
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+    pub outline_style: StrokeStyle,
+    pub outline_alignment: OutlineAlignment,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    geometry: SharedGeometry,
+}
+
+impl Default for PathShape {
+    fn default() -> Self {
+        let geometry = SharedGeometry::default();
+
+        Self {
+            path: Path::new(),
+            fill_rule: FillRule::default(),
+            fill: Fill::default(),
+            outline_thickness: 0.0,
+            outline_color: Color::WHITE,
+            outline_style: StrokeStyle::default(),
+            outline_alignment: OutlineAlignment::default(),
+            blend_mode: BlendMode::default(),
+            material: None,
+            geometry,
+        }
+    }
+}
+
+impl PathShape {
+    pub fn vertices(&self) -> &[GeometryVertex] {
+        self.geometry.vertices()
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        self.geometry.indices()
+    }
+
+    /// The geometry to draw the shape with at `screen_scale` times its size, if it differs from
+    /// its own.
+    pub(crate) fn lod(&self, screen_scale: f32) -> Option<Arc<VertexBuffers<GeometryVertex, u16>>> {
+        self.geometry.lod(screen_scale)
+    }
+
+    /// The first subpath flattened to a polygon in local coordinates, or `None` if it isn't
+    /// closed.
+    pub fn outline(&self) -> Option<Vec<Vec2>> {
+        flatten_outline(&self.path)
+    }
+
+    /// Combines the area of this shape with `other`'s, e.g. to punch a hole into it or carve
+    /// terrain. Both shapes' paths are taken to be in the same local coordinates, and each one's
+    /// fill rule decides what its area is. The result is made of straight lines, keeps this
+    /// shape's style and has to be updated before it is drawn.
+    pub fn boolean(&self, other: &PathShape, op: BooleanOp) -> PathShape {
+        let subject = overlay_shapes(&self.path, self.fill_rule);
+        let clip = overlay_shapes(&other.path, other.fill_rule);
+        let shapes = subject.overlay(
+            &clip,
+            op.into(),
+            i_overlay::core::fill_rule::FillRule::NonZero,
+        );
+
+        let mut builder = Path::builder();
+        for contour in shapes.iter().flatten() {
+            if let [first, rest @ ..] = contour.as_slice() {
+                builder.begin(point(first[0], first[1]));
+                for p in rest {
+                    builder.line_to(point(p[0], p[1]));
+                }
+                builder.end(true);
+            }
+        }
+
+        PathShape {
+            path: builder.build(),
+            // The contours never overlap, so holes stay holes whichever way they run.
+            fill_rule: FillRule::EvenOdd,
+            geometry: SharedGeometry::default(),
+            ..self.clone()
+        }
+    }
+}
+
+/// How [`PathShape::boolean`] combines the areas of two shapes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// The area covered by either shape.
+    Union,
+    /// The area covered by both shapes.
+    Intersection,
+    /// The area of the first shape that the second doesn't cover.
+    Difference,
+    /// The area covered by exactly one of the shapes.
+    Xor,
+}
+
+impl From<BooleanOp> for OverlayRule {
+    fn from(op: BooleanOp) -> Self {
+        match op {
+            BooleanOp::Union => OverlayRule::Union,
+            BooleanOp::Intersection => OverlayRule::Intersect,
+            BooleanOp::Difference => OverlayRule::Difference,
+            BooleanOp::Xor => OverlayRule::Xor,
+        }
+    }
+}
+
+/// Curves are flattened to within this distance before shapes are combined.
+const BOOLEAN_TOLERANCE: f32 = 0.05;
+
+/// The area of `path` under `fill_rule` as outlines and holes that don't overlap. Open subpaths
+/// are closed like the tessellator does when filling them.
+fn overlay_shapes(path: &Path, fill_rule: FillRule) -> Vec<Vec<Vec<[f32; 2]>>> {
+    let mut contours = Vec::new();
+    let mut contour = Vec::new();
+    for event in path.iter().flattened(BOOLEAN_TOLERANCE) {
+        match event {
+            PathEvent::Begin { at } => contour.push([at.x, at.y]),
+            PathEvent::Line { to, .. } => contour.push([to.x, to.y]),
+            PathEvent::End { .. } => {
+                let contour = std::mem::take(&mut contour);
+                if contour.len() >= 3 {
+                    contours.push(contour);
+                }
+            }
+            PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => {}
+        }
+    }
+
+    contours.simplify_shape(fill_rule.into())
+}
+
+/// Curves are flattened to within this distance for shapes' outlines.
+const OUTLINE_TOLERANCE: f32 = 0.5;
+
+/// The first subpath of `path` flattened to a polygon, or `None` if it isn't closed.
+fn flatten_outline(path: &Path) -> Option<Vec<Vec2>> {
+    let mut points = Vec::new();
+    for event in path.iter().flattened(OUTLINE_TOLERANCE) {
+        match event {
+            PathEvent::Begin { at } => points.push(Vec2::new(at.x, at.y)),
+            PathEvent::Line { to, .. } => points.push(Vec2::new(to.x, to.y)),
+            PathEvent::End { close, .. } => {
+                return (close && points.len() >= 3).then_some(points);
+            }
+            PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => {}
+        }
+    }
+
+    None
+}
+
+impl Geometry for PathShape {
+    fn update(&mut self, tessellator: &mut Tessellator) -> Result<(), GeometryError> {
+        self.geometry.validate(
+            check_finite(
+                "PathShape",
+                "path",
+                self.path
+                    .iter()
+                    .flat_map(|event| [event.from(), event.to()])
+                    .flat_map(|p| p.to_array()),
+            )
+            .and_then(|()| check_outline("PathShape", self.outline_thickness, &self.outline_style)),
+        )?;
+
+        let tessellation = Tessellation::Shape {
+            path: self.path.clone(),
+            fill_rule: self.fill_rule,
+            fill: self.fill.clone(),
+            outline_color: self.outline_color,
+            outline_thickness: self.outline_thickness,
+            outline_style: self.outline_style.clone(),
+            outline_alignment: self.outline_alignment,
+        };
+        self.geometry.update(tessellator, tessellation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Drawable, Transform};
+
+    fn bounds(vertices: &[GeometryVertex]) -> (Vec2, Vec2) {
+        vertices.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), v| (min.min(v.position), max.max(v.position)),
+        )
+    }
+
+    fn assert_bounds(vertices: &[GeometryVertex], min: Vec2, max: Vec2) {
+        assert_bounds_near(vertices, min, max, 1e-3);
+    }
+
+    fn assert_bounds_near(vertices: &[GeometryVertex], min: Vec2, max: Vec2, tolerance: f32) {
+        let bounds = bounds(vertices);
+        assert!(
+            bounds.0.abs_diff_eq(min, tolerance) && bounds.1.abs_diff_eq(max, tolerance),
+            "expected bounds {:?}, got {:?}",
+            (min, max),
+            bounds
+        );
+    }
+
+    fn outlined_rect(alignment: OutlineAlignment) -> RectangleShape {
+        let mut rect = RectangleShape {
+            size: Vec2::new(200.0, 200.0),
+            outline_thickness: 5.0,
+            outline_alignment: alignment,
+            ..Default::default()
+        };
+        rect.update(&mut Tessellator::new(0.1)).unwrap();
+        rect
+    }
+
+    #[test]
+    fn inside_outline_keeps_size() {
+        let rect = outlined_rect(OutlineAlignment::Inside);
+        assert_bounds(rect.vertices(), Vec2::ZERO, Vec2::splat(200.0));
+    }
+
+    #[test]
+    fn centered_outline_straddles_edge() {
+        let rect = outlined_rect(OutlineAlignment::Centered);
+        assert_bounds(rect.vertices(), Vec2::splat(-2.5), Vec2::splat(202.5));
+    }
+
+    #[test]
+    fn outside_outline_grows_shape() {
+        let rect = outlined_rect(OutlineAlignment::Outside);
+        assert_bounds(rect.vertices(), Vec2::splat(-5.0), Vec2::splat(205.0));
+    }
+
+    #[test]
+    fn alignment_ignores_path_direction() {
+        let mut builder = PathBuilder::new();
+        builder
+            .move_to(Vec2::ZERO)
+            .line_to(Vec2::new(0.0, 200.0))
+            .line_to(Vec2::new(200.0, 200.0))
+            .line_to(Vec2::new(200.0, 0.0))
+            .close();
+
+        let mut path = PathShape {
+            path: builder.build(),
+            outline_thickness: 5.0,
+            outline_alignment: OutlineAlignment::Outside,
+            ..Default::default()
+        };
+        path.update(&mut Tessellator::new(0.1)).unwrap();
 
-        tessellator.tesselate(
-            &path,
-            self.fill_color,
-            self.outline_color,
-            self.outline_thickness,
-            &mut self.geometry,
+        assert_bounds(path.vertices(), Vec2::splat(-5.0), Vec2::splat(205.0));
+    }
+
+    #[test]
+    fn dashed_outline_keeps_alignment() {
+        let mut rect = RectangleShape {
+            size: Vec2::new(200.0, 200.0),
+            fill: Color::new(0.0, 0.0, 0.0, 0.0).into(),
+            outline_thickness: 5.0,
+            outline_alignment: OutlineAlignment::Outside,
+            ..Default::default()
+        };
+        rect.outline_style.dashes = vec![10.0, 10.0];
+        rect.update(&mut Tessellator::new(0.1)).unwrap();
+
+        let (min, max) = bounds(rect.vertices());
+        assert!(min.cmple(Vec2::splat(-4.999)).all() && max.cmpge(Vec2::splat(204.999)).all());
+        assert!(rect
+            .vertices()
+            .iter()
+            .all(|v| v.position.cmpge(Vec2::splat(-5.001)).all()
+                && v.position.cmple(Vec2::splat(205.001)).all()));
+    }
+
+    #[test]
+    fn lods_follow_screen_scale() {
+        let mut circle = CircleShape {
+            radius: 100.0,
+            ..Default::default()
+        };
+        circle.update(&mut Tessellator::new(0.5)).unwrap();
+
+        assert!(circle.lod(1.0).is_none());
+        let larger = circle.lod(8.0).unwrap();
+        let smaller = circle.lod(0.1).unwrap();
+        assert!(larger.vertices.len() > circle.vertices().len());
+        assert!(smaller.vertices.len() < circle.vertices().len());
+        assert!(Arc::ptr_eq(&larger, &circle.lod(7.0).unwrap()));
+    }
+
+    #[test]
+    fn invalid_shapes_report_errors_and_drop_geometry() {
+        let mut tessellator = Tessellator::new(0.1);
+        let mut polygon = PolygonShape {
+            radius: 50.0,
+            point_count: 5,
+            ..Default::default()
+        };
+        polygon.update(&mut tessellator).unwrap();
+        assert!(!polygon.vertices().is_empty());
+
+        polygon.point_count = 2;
+        assert!(matches!(
+            polygon.update(&mut tessellator),
+            Err(GeometryError::InvalidShape { .. })
+        ));
+        assert!(polygon.vertices().is_empty() && polygon.lod(4.0).is_none());
+
+        let mut circle = CircleShape {
+            radius: f32::NAN,
+            ..Default::default()
+        };
+        assert!(circle.update(&mut tessellator).is_err());
+        assert!(circle.vertices().is_empty());
+    }
+
+    #[test]
+    fn negative_radii_and_inverted_rings_are_invalid() {
+        let mut tessellator = Tessellator::new(0.1);
+        let invalid = |result: Result<(), GeometryError>| {
+            matches!(result, Err(GeometryError::InvalidShape { .. }))
+        };
+
+        let mut circle = CircleShape {
+            radius: -10.0,
+            ..Default::default()
+        };
+        assert!(invalid(circle.update(&mut tessellator)));
+        let mut arc = ArcShape {
+            radius: -10.0,
+            sweep_angle: 90.0,
+            ..Default::default()
+        };
+        assert!(invalid(arc.update(&mut tessellator)));
+        let mut sector = SectorShape {
+            radius: -10.0,
+            sweep_angle: 90.0,
+            ..Default::default()
+        };
+        assert!(invalid(sector.update(&mut tessellator)));
+
+        let mut ring = RingShape {
+            radius: 50.0,
+            inner_radius: 20.0,
+            ..Default::default()
+        };
+        ring.update(&mut tessellator).unwrap();
+        assert!(!ring.vertices().is_empty());
+        for (radius, inner_radius) in [(50.0, 50.0), (50.0, 80.0), (50.0, -20.0), (-50.0, 20.0)] {
+            ring.radius = radius;
+            ring.inner_radius = inner_radius;
+            assert!(invalid(ring.update(&mut tessellator)));
+            assert!(ring.vertices().is_empty());
+        }
+
+        // Without a hole, a ring is a circle.
+        ring.radius = 50.0;
+        ring.inner_radius = 0.0;
+        ring.update(&mut tessellator).unwrap();
+    }
+
+    #[test]
+    fn identical_shapes_share_meshes() {
+        let mut tessellator = Tessellator::new(0.1);
+        let circle = |radius| CircleShape {
+            radius,
+            fill: Color::WHITE.into(),
+            ..Default::default()
+        };
+        let mut circles = vec![circle(20.0), circle(20.0), circle(30.0)];
+        for circle in &mut circles {
+            circle.update(&mut tessellator).unwrap();
+        }
+
+        let copy = circles[0].clone();
+        assert_eq!(
+            circles[0].vertices().as_ptr(),
+            circles[1].vertices().as_ptr()
+        );
+        assert_eq!(circles[0].vertices().as_ptr(), copy.vertices().as_ptr());
+        assert_ne!(
+            circles[0].vertices().as_ptr(),
+            circles[2].vertices().as_ptr()
+        );
+        assert_eq!(tessellator.cached_meshes(), 2);
+
+        circles.truncate(1);
+        drop(copy);
+        assert_eq!(tessellator.cached_meshes(), 1);
+    }
+
+    #[test]
+    fn arcs_run_clockwise_from_the_top() {
+        let mut arc = ArcShape {
+            radius: 100.0,
+            sweep_angle: 90.0,
+            outline_thickness: 2.0,
+            ..Default::default()
+        };
+        arc.update(&mut Tessellator::new(0.01)).unwrap();
+        // From the top of the circle to its right, with butt caps at both ends.
+        assert_bounds_near(arc.vertices(), Vec2::splat(100.0), Vec2::splat(201.0), 0.05);
+
+        arc.start_angle = 180.0;
+        arc.update(&mut Tessellator::new(0.01)).unwrap();
+        assert_bounds_near(arc.vertices(), Vec2::splat(-1.0), Vec2::splat(100.0), 0.05);
+    }
+
+    #[test]
+    fn sectors_cover_their_sweep() {
+        let mut tessellator = Tessellator::new(0.01);
+        let mut sector = SectorShape {
+            radius: 100.0,
+            start_angle: 90.0,
+            sweep_angle: 90.0,
+            ..Default::default()
+        };
+        sector.update(&mut tessellator).unwrap();
+        let quarter = std::f32::consts::PI * 10000.0 / 4.0;
+        assert_bounds(
+            sector.vertices(),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(200.0, 100.0),
+        );
+        // Arcs are approximated with curves, which bulge out very slightly.
+        assert!((area(sector.vertices(), sector.indices()) / quarter - 1.0).abs() < 0.01);
+
+        // Negative sweeps run counter-clockwise.
+        sector.start_angle = 0.0;
+        sector.sweep_angle = -90.0;
+        sector.update(&mut tessellator).unwrap();
+        assert_bounds(
+            sector.vertices(),
+            Vec2::new(0.0, 100.0),
+            Vec2::new(100.0, 200.0),
+        );
+
+        // Sweeps of a full turn or more draw the whole circle.
+        sector.sweep_angle = 720.0;
+        sector.update(&mut tessellator).unwrap();
+        let circle = std::f32::consts::PI * 10000.0;
+        assert!((area(sector.vertices(), sector.indices()) / circle - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn polylines_vary_thickness_and_color_per_point() {
+        let mut polyline = PolylineShape {
+            points: vec![Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(200.0, 0.0)],
+            thicknesses: vec![2.0, 10.0],
+            colors: vec![Color::RED],
+            outline_thickness: 4.0,
+            outline_color: Color::BLUE,
+            ..Default::default()
+        };
+        polyline.update(&mut Tessellator::new(0.1)).unwrap();
+
+        // Points without a thickness or color use the outline's.
+        assert_eq!(polyline.attributes(0), [2.0, 1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(polyline.attributes(1), [10.0, 0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(polyline.attributes(2), [4.0, 0.0, 0.0, 1.0, 1.0]);
+
+        let at = |x: f32| {
+            let vertices: Vec<_> = polyline
+                .vertices()
+                .iter()
+                .filter(|v| (v.position.x - x).abs() < 0.1)
+                .collect();
+            let half_width = vertices
+                .iter()
+                .map(|v| v.position.y.abs())
+                .fold(0.0, f32::max);
+            (half_width, vertices[0].color)
+        };
+        let (start_width, start_color) = at(0.0);
+        let (middle_width, middle_color) = at(100.0);
+        let (end_width, end_color) = at(200.0);
+        // Widths change along the line, so the stroke's edges are slightly tilted.
+        assert!((start_width - 1.0).abs() < 0.01, "{}", start_width);
+        assert!((middle_width - 5.0).abs() < 0.01, "{}", middle_width);
+        assert!((end_width - 2.0).abs() < 0.01, "{}", end_width);
+        assert_color_eq(start_color, Color::RED);
+        assert_color_eq(middle_color, Color::BLUE);
+        assert_color_eq(end_color, Color::BLUE);
+
+        polyline.colors = vec![Color::new(f32::NAN, 0.0, 0.0, 1.0)];
+        assert!(matches!(
+            polyline.update(&mut Tessellator::new(0.1)),
+            Err(GeometryError::InvalidShape { .. })
+        ));
+    }
+
+    #[test]
+    fn polylines_close_and_need_two_points() {
+        let mut tessellator = Tessellator::new(0.1);
+        let mut polyline = PolylineShape {
+            points: vec![Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(0.0, 100.0)],
+            outline_thickness: 4.0,
+            ..Default::default()
+        };
+        let on_closing_edge = Vec2::new(0.0, 50.0);
+
+        polyline.update(&mut tessellator).unwrap();
+        let open = Drawable::Polyline(polyline.clone());
+        assert!(!open.contains_point(on_closing_edge, &Transform::default()));
+
+        polyline.closed = true;
+        polyline.update(&mut tessellator).unwrap();
+        let closed = Drawable::Polyline(polyline.clone());
+        assert!(closed.contains_point(on_closing_edge, &Transform::default()));
+
+        polyline.points.truncate(1);
+        polyline.update(&mut tessellator).unwrap();
+        assert!(polyline.vertices().is_empty());
+    }
+
+    #[test]
+    fn rings_leave_a_hole() {
+        let mut ring = RingShape {
+            radius: 100.0,
+            inner_radius: 50.0,
+            ..Default::default()
+        };
+        ring.update(&mut Tessellator::new(0.01)).unwrap();
+        let expected = std::f32::consts::PI * (10000.0 - 2500.0);
+        assert_bounds(ring.vertices(), Vec2::ZERO, Vec2::splat(200.0));
+        assert!((area(ring.vertices(), ring.indices()) - expected).abs() < 5.0);
+
+        let ring = Drawable::Ring(ring);
+        let transform = Transform::default();
+        assert!(!ring.contains_point(Vec2::splat(100.0), &transform));
+        assert!(!ring.contains_point(Vec2::new(140.0, 100.0), &transform));
+        assert!(ring.contains_point(Vec2::new(175.0, 100.0), &transform));
+    }
+
+    fn area(vertices: &[GeometryVertex], indices: &[u16]) -> f32 {
+        indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] as usize].position);
+                (b - a).perp_dot(c - a).abs() / 2.0
+            })
+            .sum()
+    }
+
+    fn filled_area(shape: &PathShape) -> f32 {
+        let mut shape = shape.clone();
+        shape.update(&mut Tessellator::new(0.01)).unwrap();
+        area(shape.vertices(), shape.indices())
+    }
+
+    fn path_shape(build: impl FnOnce(&mut PathBuilder)) -> PathShape {
+        let mut builder = PathBuilder::new();
+        build(&mut builder);
+        PathShape {
+            path: builder.build(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn boolean_operations_combine_areas() {
+        let square = path_shape(|b| {
+            b.rectangle(Vec2::ZERO, Vec2::splat(100.0));
+        });
+        let shifted = path_shape(|b| {
+            b.rectangle(Vec2::new(50.0, 0.0), Vec2::splat(100.0));
+        });
+        let hole = path_shape(|b| {
+            b.circle(Vec2::splat(50.0), 20.0);
+        });
+
+        let area = |op| filled_area(&square.boolean(&shifted, op));
+        assert!((area(BooleanOp::Union) - 15000.0).abs() < 1.0);
+        assert!((area(BooleanOp::Intersection) - 5000.0).abs() < 1.0);
+        assert!((area(BooleanOp::Difference) - 5000.0).abs() < 1.0);
+        assert!((area(BooleanOp::Xor) - 10000.0).abs() < 1.0);
+
+        let holed = square.boolean(&hole, BooleanOp::Difference);
+        let expected = 10000.0 - std::f32::consts::PI * 400.0;
+        assert!((filled_area(&holed) - expected).abs() < 5.0);
+    }
+
+    #[test]
+    fn boolean_operations_respect_fill_rules() {
+        // Two nested squares that only leave a frame under the even-odd rule.
+        let mut frame = path_shape(|b| {
+            b.rectangle(Vec2::ZERO, Vec2::splat(100.0))
+                .rectangle(Vec2::splat(25.0), Vec2::splat(50.0));
+        });
+        let square = path_shape(|b| {
+            b.rectangle(Vec2::ZERO, Vec2::splat(100.0));
+        });
+
+        frame.fill_rule = FillRule::EvenOdd;
+        let area = filled_area(&frame.boolean(&square, BooleanOp::Intersection));
+        assert!((area - 7500.0).abs() < 1.0);
+
+        frame.fill_rule = FillRule::NonZero;
+        let area = filled_area(&frame.boolean(&square, BooleanOp::Intersection));
+        assert!((area - 10000.0).abs() < 1.0);
+    }
+
+    fn assert_color_eq(a: Color, b: Color) {
+        let close = a
+            .to_array()
+            .iter()
+            .zip(b.to_array())
+            .all(|(a, b)| (a - b).abs() < 1e-4);
+        assert!(close, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn hex_colors_are_srgb() {
+        assert_color_eq(Color::from_hex("#ffa500").unwrap(), Color::ORANGE);
+        assert_color_eq(Color::from_hex("808080").unwrap(), Color::GRAY);
+        assert_color_eq("#f0f".parse().unwrap(), Color::MAGENTA);
+        assert_color_eq(
+            Color::from_hex("#00000080").unwrap(),
+            Color::new(0.0, 0.0, 0.0, 128.0 / 255.0),
+        );
+        assert_eq!(
+            Color::from_hex("#12345"),
+            Err(ParseColorError::InvalidLength(5))
+        );
+        assert_eq!(
+            Color::from_hex("#12g"),
+            Err(ParseColorError::InvalidDigit('g'))
+        );
+    }
+
+    #[test]
+    fn color_conversions_round_trip() {
+        let color = Color::from_srgb(0.8, 0.3, 0.5, 0.7);
+        assert_color_eq(color.to_srgb(), Color::new(0.8, 0.3, 0.5, 0.7));
+        assert_color_eq(Color::new(0.8, 0.3, 0.5, 0.7).to_linear(), color);
+
+        let (h, s, v) = color.to_hsv();
+        assert_color_eq(Color::from_hsv(h, s, v, 0.7), color);
+        let (h, s, l) = color.to_hsl();
+        assert_color_eq(Color::from_hsl(h, s, l, 0.7), color);
+
+        assert_color_eq(Color::from_hsv(120.0, 1.0, 1.0, 1.0), Color::GREEN);
+        let gray = Color::from_srgb(0.5, 0.5, 0.5, 1.0);
+        assert_color_eq(Color::from_hsl(0.0, 0.0, 0.5, 1.0), gray);
+        assert_color_eq(Color::BLACK.mix(Color::WHITE, 0.5), gray);
+    }
+
+    /// The start and end x of every dash of a horizontal line 100 long.
+    fn dashes(dashes: Vec<f32>, dash_offset: f32) -> Option<Vec<(f32, f32)>> {
+        let mut builder = Path::builder();
+        builder.begin(point(0.0, 0.0));
+        builder.line_to(point(100.0, 0.0));
+        builder.end(false);
+        let style = StrokeStyle {
+            dashes,
+            dash_offset,
+            ..Default::default()
+        };
+
+        let dashed = dash(&builder.build(), &style, 0.01)?;
+        let mut ranges = Vec::new();
+        for event in dashed.iter() {
+            match event {
+                PathEvent::Begin { at } => ranges.push((at.x, at.x)),
+                PathEvent::Line { to, .. } => ranges.last_mut().unwrap().1 = to.x,
+                _ => {}
+            }
+        }
+        Some(ranges)
+    }
+
+    fn assert_ranges_eq(actual: &[(f32, f32)], expected: &[(f32, f32)]) {
+        let close = actual.len() == expected.len()
+            && actual
+                .iter()
+                .zip(expected)
+                .all(|(a, b)| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3);
+        assert!(close, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn dash_patterns_repeat_along_the_path() {
+        let ranges = dashes(vec![10.0, 5.0], 0.0).unwrap();
+        let expected: Vec<_> = (0..7)
+            .map(|i| (i as f32 * 15.0, i as f32 * 15.0 + 10.0))
+            .collect();
+        assert_ranges_eq(&ranges, &expected);
+    }
+
+    #[test]
+    fn dash_offset_shifts_the_pattern() {
+        let ranges = dashes(vec![10.0, 10.0], 5.0).unwrap();
+        assert_ranges_eq(
+            &ranges,
+            &[
+                (0.0, 5.0),
+                (15.0, 25.0),
+                (35.0, 45.0),
+                (55.0, 65.0),
+                (75.0, 85.0),
+                (95.0, 100.0),
+            ],
+        );
+
+        // Offsets wrap around the period, including negative ones.
+        assert_ranges_eq(&dashes(vec![10.0, 10.0], 25.0).unwrap(), &ranges);
+        assert_ranges_eq(&dashes(vec![10.0, 10.0], -15.0).unwrap(), &ranges);
+    }
+
+    #[test]
+    fn odd_dash_patterns_are_repeated() {
+        let ranges = dashes(vec![10.0], 0.0).unwrap();
+        assert_ranges_eq(
+            &ranges,
+            &[
+                (0.0, 10.0),
+                (20.0, 30.0),
+                (40.0, 50.0),
+                (60.0, 70.0),
+                (80.0, 90.0),
+            ],
+        );
+
+        // Dashes and gaps swap on every repetition of the pattern.
+        let ranges = dashes(vec![20.0, 10.0, 5.0], 0.0).unwrap();
+        assert_ranges_eq(
+            &ranges,
+            &[(0.0, 20.0), (30.0, 35.0), (55.0, 65.0), (70.0, 90.0)],
+        );
+    }
+
+    #[test]
+    fn invalid_dash_patterns_draw_solid_lines() {
+        assert!(dashes(vec![], 0.0).is_none());
+        assert!(dashes(vec![0.0, 0.0], 0.0).is_none());
+        assert!(dashes(vec![10.0, -5.0], 0.0).is_none());
+
+        let line = |dashes| {
+            let mut line = LineShape {
+                length: 100.0,
+                outline_thickness: 2.0,
+                ..Default::default()
+            };
+            line.outline_style.dashes = dashes;
+            line.update(&mut Tessellator::new(0.1)).unwrap();
+            line
+        };
+        let solid = line(Vec::new());
+        let zero_period = line(vec![0.0, 0.0]);
+        let negative = line(vec![10.0, -5.0]);
+        assert_eq!(zero_period.vertices().len(), solid.vertices().len());
+        assert_eq!(negative.vertices().len(), solid.vertices().len());
+        assert_eq!(zero_period.indices(), solid.indices());
+    }
+
+    fn filled_square(fill: Fill) -> RectangleShape {
+        let mut rect = RectangleShape {
+            size: Vec2::splat(200.0),
+            fill,
+            ..Default::default()
+        };
+        rect.update(&mut Tessellator::new(0.1)).unwrap();
+        rect
+    }
+
+    /// The color of the vertex at `position`, which the fill must have subdivided the shape at.
+    fn color_at_vertex(shape: &RectangleShape, position: Vec2) -> Color {
+        shape
+            .vertices()
+            .iter()
+            .find(|v| v.position.abs_diff_eq(position, 1e-3))
+            .unwrap_or_else(|| panic!("no vertex at {}", position))
+            .color
+    }
+
+    #[test]
+    fn linear_gradients_blend_along_their_axis() {
+        let rect = filled_square(Fill::LinearGradient {
+            start: Vec2::new(50.0, 0.0),
+            end: Vec2::new(150.0, 0.0),
+            start_color: Color::BLACK,
+            end_color: Color::WHITE,
+        });
+
+        let gray = Color::new(0.5, 0.5, 0.5, 1.0);
+        assert_color_eq(color_at_vertex(&rect, Vec2::new(50.0, 100.0)), Color::BLACK);
+        assert_color_eq(color_at_vertex(&rect, Vec2::new(100.0, 25.0)), gray);
+        assert_color_eq(
+            color_at_vertex(&rect, Vec2::new(150.0, 200.0)),
+            Color::WHITE,
+        );
+        // Both colors extend beyond the ends.
+        assert_color_eq(color_at_vertex(&rect, Vec2::ZERO), Color::BLACK);
+        assert_color_eq(color_at_vertex(&rect, Vec2::splat(200.0)), Color::WHITE);
+    }
+
+    #[test]
+    fn radial_gradients_blend_outwards() {
+        let rect = filled_square(Fill::RadialGradient {
+            center: Vec2::splat(100.0),
+            radius: 50.0,
+            inner_color: Color::RED,
+            outer_color: Color::BLUE,
+        });
+
+        let purple = Color::new(0.5, 0.0, 0.5, 1.0);
+        assert_color_eq(color_at_vertex(&rect, Vec2::splat(100.0)), Color::RED);
+        assert_color_eq(color_at_vertex(&rect, Vec2::new(125.0, 100.0)), purple);
+        assert_color_eq(color_at_vertex(&rect, Vec2::new(100.0, 50.0)), Color::BLUE);
+        assert_color_eq(color_at_vertex(&rect, Vec2::ZERO), Color::BLUE);
+    }
+
+    #[test]
+    fn custom_fills_color_every_vertex() {
+        let rect = filled_square(Fill::Custom(Arc::new(|position| {
+            Color::new(position.x / 200.0, position.y / 200.0, 0.0, 1.0)
+        })));
+
+        // A plain rectangle has four vertices; custom fills are always subdivided.
+        assert!(rect.vertices().len() > 4);
+        for vertex in rect.vertices() {
+            let expected = Color::new(
+                vertex.position.x / 200.0,
+                vertex.position.y / 200.0,
+                0.0,
+                1.0,
+            );
+            assert_color_eq(vertex.color, expected);
+        }
+        assert_color_eq(
+            color_at_vertex(&rect, Vec2::splat(100.0)),
+            Color::new(0.5, 0.5, 0.0, 1.0),
         );
     }
 }
@@ -25,6 +25,10 @@ impl<'frame> InputHelper<'frame> {
         self.winit_helper.key_released(key_code.into())
     }
 
+    pub fn key_held(&self, key_code: KeyCode) -> bool {
+        self.winit_helper.key_held(key_code.into())
+    }
+
     pub fn mouse_pressed(&self, button: MouseButton) -> bool {
         self.winit_helper.mouse_pressed(button.into())
     }
@@ -39,9 +43,12 @@ impl<'frame> InputHelper<'frame> {
         Vec2::new(x, y)
     }
 
+    /// The world position under the cursor as seen by `camera`, even if the cursor is outside of
+    /// the camera's viewport.
     pub fn mouse_in_world(&self, camera: &Camera) -> Vec2 {
-        let viewport_position = self.mouse_in_viewport();
-        let viewport_dimensions = Vec2::new(camera.width(), camera.height());
+        let viewport = camera.viewport_in_pixels();
+        let viewport_position = self.mouse_in_viewport() - viewport.position;
+        let viewport_dimensions = viewport.size;
         let mut ndc = viewport_position / viewport_dimensions * 2.0 - 1.0;
         ndc.y *= -1.0;
         let ndc = Vec4::from((ndc, 1.0, 1.0));
@@ -53,6 +60,18 @@ impl<'frame> InputHelper<'frame> {
 
         mouse_position.xy()
     }
+
+    /// Finds the camera whose viewport the cursor is over, returning its index and the world
+    /// position under the cursor. Later cameras take precedence where viewports overlap.
+    pub fn mouse_in_viewports(&self, cameras: &[Camera]) -> Option<(usize, Vec2)> {
+        let mouse = self.mouse_in_viewport();
+        cameras
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, camera)| camera.viewport_in_pixels().contains(mouse))
+            .map(|(i, camera)| (i, self.mouse_in_world(camera)))
+    }
 }
 
 // Copied from winit.
@@ -244,9 +263,9 @@ pub enum KeyCode {
     Cut,
 }
 
-impl Into<VirtualKeyCode> for KeyCode {
-    fn into(self) -> VirtualKeyCode {
-        match self {
+impl From<KeyCode> for VirtualKeyCode {
+    fn from(key_code: KeyCode) -> Self {
+        match key_code {
             KeyCode::Key1 => VirtualKeyCode::Key1,
             KeyCode::Key2 => VirtualKeyCode::Key2,
             KeyCode::Key3 => VirtualKeyCode::Key3,
@@ -422,9 +441,9 @@ pub enum MouseButton {
     Number(u32),
 }
 
-impl Into<usize> for MouseButton {
-    fn into(self) -> usize {
-        match self {
+impl From<MouseButton> for usize {
+    fn from(button: MouseButton) -> Self {
+        match button {
             MouseButton::Left => 0,
             MouseButton::Middle => 2,
             MouseButton::Right => 1,
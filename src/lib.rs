@@ -1,44 +1,66 @@
-use std::time::{Duration, Instant};
+use std::{
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
+use animation::Animator;
+use capture::FrameCapture;
 use components::{compute_transformation_matrix, Drawable, Transform};
 pub use env_logger::init as init_logger;
+pub use error::{
+    GeometryError, PapercutError, ParseColorError, SpriteSheetError, SvgError, TextureError,
+};
 use futures::executor::block_on;
-use glam::{Vec2, Vec4};
-use graphics::Color;
+use glam::{Vec2, Vec4, Vec4Swizzles};
+use graphics::{BlendMode, Color, Rect};
 use input::InputHelper;
-use renderer::{Globals, GraphicsDevice, Renderer, Vertex};
+use lighting::{Light, LightRenderer, SceneLight};
+use material::MaterialInstance;
+use particles::ParticleEmitter;
+use post_processing::{PostEffect, PostProcessor};
+use renderer::{DrawBatch, Globals, GraphicsDevice, Renderer, TargetPass, Vertex, View};
+use texture::{RenderTarget, Texture};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BufferAddress, BufferUsages, COPY_BUFFER_ALIGNMENT,
+    BufferAddress, BufferUsages,
+};
+use winit::{
+    dpi::PhysicalSize, event_loop::EventLoop, platform::run_return::EventLoopExtRunReturn,
+    window::WindowBuilder,
 };
-use winit::{dpi::PhysicalSize, event_loop::EventLoop, window::WindowBuilder};
 use winit_input_helper::WinitInputHelper;
 
-use crate::camera::Camera;
+use crate::camera::{Camera, MAX_CAMERAS};
 
 const ASPECT_RATIO: f32 = 16_f32 / 9_f32;
 pub const DEFAULT_WINDOW_WIDTH: f32 = 1024.0;
-pub const DEFAULT_WINDOW_HEIGHT: f32 = DEFAULT_WINDOW_WIDTH as f32 / ASPECT_RATIO;
+pub const DEFAULT_WINDOW_HEIGHT: f32 = DEFAULT_WINDOW_WIDTH / ASPECT_RATIO;
 pub const DEFAULT_TITLE: &str = "Papercut2D";
 
+pub mod animation;
 pub mod camera;
+mod capture;
 pub mod components;
+mod error;
 pub mod graphics;
 pub mod input;
+pub mod lighting;
+pub mod material;
+pub mod particles;
+pub mod post_processing;
 mod renderer;
+pub mod svg;
+pub mod texture;
+pub mod tween;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub enum Fullscreen {
     Exclusive,
+    #[default]
     Borderless,
 }
 
-impl Default for Fullscreen {
-    fn default() -> Self {
-        Fullscreen::Borderless
-    }
-}
-
 #[derive(Debug)]
 pub struct WindowConfig {
     pub title: String,
@@ -63,16 +85,65 @@ impl Default for WindowConfig {
     }
 }
 
+/// Whether the frame is written in sRGB, which the display gamma-corrects, or as linear values.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ColorFormat {
+    #[default]
+    Srgb,
+    Unorm,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum PowerPreference {
+    #[default]
+    LowPower,
+    HighPerformance,
+}
+
+/// The graphics API to render with. `Auto` lets wgpu pick the best one for the platform.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Auto,
+    Vulkan,
+    Metal,
+    Dx12,
+    Dx11,
+    Gl,
+}
+
 #[derive(Debug)]
 pub struct RendererConfig {
     pub clear_color: Color,
+    /// Full-screen effects applied to each frame, in order.
+    pub post_effects: Vec<PostEffect>,
+    /// Number of MSAA samples, 1 disables MSAA. Unsupported counts fall back to the closest
+    /// supported one.
+    pub sample_count: u32,
+    pub color_format: ColorFormat,
+    /// Only used when the renderer starts.
+    pub power_preference: PowerPreference,
+    /// Only used when the renderer starts.
+    pub backend: Backend,
 }
 
 impl Default for RendererConfig {
     fn default() -> Self {
         let clear_color = Color::new(1.0, 0.0, 1.0, 1.0);
+        let post_effects = Vec::new();
+        let sample_count = 4;
+        let color_format = ColorFormat::default();
+        let power_preference = PowerPreference::default();
+        let backend = Backend::default();
 
-        Self { clear_color }
+        Self {
+            clear_color,
+            post_effects,
+            sample_count,
+            color_format,
+            power_preference,
+            backend,
+        }
     }
 }
 
@@ -80,9 +151,25 @@ impl Default for RendererConfig {
 pub struct Context {
     window_title: String,
     window_size: Vec2,
+    sample_count: u32,
+    color_format: ColorFormat,
+    render_settings_changed: bool,
+    cameras: Vec<Camera>,
+    /// The largest zoom of the cameras drawing the frame, see [`Context::draw_shape`].
+    zoom: f32,
+    screenshots: Vec<PathBuf>,
+    capture: Option<Capture>,
 }
 
-impl<'frame> Context {
+/// An image sequence being recorded, see [`Context::start_capture`].
+#[derive(Debug)]
+struct Capture {
+    directory: PathBuf,
+    frame_time: Duration,
+    frame: u32,
+}
+
+impl Context {
     pub fn window_size(&self) -> Vec2 {
         self.window_size
     }
@@ -91,58 +178,242 @@ impl<'frame> Context {
         self.window_title = title.into();
     }
 
+    /// Splits the window between `cameras`, each drawing the scene into its own viewport, e.g. for
+    /// local co-op. Cameras are resized with the window. Without cameras the whole window is drawn
+    /// by the camera passed to [`Game::on_update`]. At most [`MAX_CAMERAS`] cameras are used.
+    pub fn set_cameras(&mut self, mut cameras: Vec<Camera>) {
+        if cameras.len() > MAX_CAMERAS {
+            log::warn!(
+                "{} cameras were set but only {} are supported",
+                cameras.len(),
+                MAX_CAMERAS
+            );
+            cameras.truncate(MAX_CAMERAS);
+        }
+
+        for camera in &mut cameras {
+            camera.resize(self.window_size.x, self.window_size.y);
+        }
+        self.cameras = cameras;
+    }
+
+    pub fn cameras(&self) -> &[Camera] {
+        &self.cameras
+    }
+
+    pub fn cameras_mut(&mut self) -> &mut [Camera] {
+        &mut self.cameras
+    }
+
+    /// Saves the next rendered frame to `path` as a PNG. The image is read back and written in
+    /// the background.
+    pub fn request_screenshot(&mut self, path: impl Into<PathBuf>) {
+        self.screenshots.push(path.into());
+    }
+
+    /// Saves every rendered frame to `directory` as `frame_00000.png`, `frame_00001.png` and so
+    /// on. While capturing, each frame advances the game by exactly `1 / frame_rate` seconds
+    /// however long it took to render, so the sequence plays back smoothly at `frame_rate`.
+    pub fn start_capture(&mut self, directory: impl Into<PathBuf>, frame_rate: u32) {
+        self.capture = Some(Capture {
+            directory: directory.into(),
+            frame_time: Duration::from_secs_f64(1.0 / frame_rate.max(1) as f64),
+            frame: 0,
+        });
+    }
+
+    pub fn stop_capture(&mut self) {
+        self.capture = None;
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// The paths the frame about to be rendered should be saved to.
+    fn take_captures(&mut self) -> Vec<PathBuf> {
+        let mut paths = std::mem::take(&mut self.screenshots);
+        if let Some(capture) = &mut self.capture {
+            paths.push(
+                capture
+                    .directory
+                    .join(format!("frame_{:05}.png", capture.frame)),
+            );
+            capture.frame += 1;
+        }
+
+        paths
+    }
+
+    /// The MSAA sample count currently in use, after validation against the adapter.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Changes the MSAA sample count from the next frame on.
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.render_settings_changed = true;
+        }
+    }
+
+    pub fn color_format(&self) -> ColorFormat {
+        self.color_format
+    }
+
+    /// Changes the color format from the next frame on.
+    pub fn set_color_format(&mut self, color_format: ColorFormat) {
+        if color_format != self.color_format {
+            self.color_format = color_format;
+            self.render_settings_changed = true;
+        }
+    }
+
+    /// Draws `drawable` with the level of detail that suits its size on screen, given by the
+    /// transform's scale and the zoom of the cameras. Shapes drawn much larger or smaller than
+    /// their own size are tessellated again the first time they are drawn at that size.
     pub fn draw_shape(&self, transform: &Transform, drawable: &Drawable, scene: &mut Scene) {
-        let t = compute_transformation_matrix(&transform);
-        let index_offset = scene.vertices.len() as u16;
-        match drawable {
-            Drawable::Circle(circle) => {
-                for v in circle.vertices() {
-                    let position = (t * Vec4::from((v.position(), 0.0, 1.0))).to_array();
-                    let color = v.color().to_array();
-                    let vertex = Vertex { position, color };
-                    scene.vertices.push(vertex);
-                }
+        self.draw_frame(transform, drawable, None, scene);
+    }
 
-                for i in circle.indices() {
-                    scene.indices.push(index_offset + i);
-                }
-            }
-            Drawable::Line(line) => {
-                for v in line.vertices() {
-                    let position = (t * Vec4::from((v.position(), 0.0, 1.0))).to_array();
-                    let color = v.color().to_array();
-                    let vertex = Vertex { position, color };
-                    scene.vertices.push(vertex);
-                }
+    /// Draws `drawable` like [`Context::draw_shape`], with sprites showing the current frame of
+    /// `animator` instead of their own source region.
+    pub fn draw_animated(
+        &self,
+        transform: &Transform,
+        drawable: &Drawable,
+        animator: &Animator,
+        scene: &mut Scene,
+    ) {
+        self.draw_frame(transform, drawable, animator.source(), scene);
+    }
 
-                for i in line.indices() {
-                    scene.indices.push(index_offset + i);
-                }
-            }
-            Drawable::Polygon(polygon) => {
-                for v in polygon.vertices() {
-                    let position = (t * Vec4::from((v.position(), 0.0, 1.0))).to_array();
-                    let color = v.color().to_array();
-                    let vertex = Vertex { position, color };
-                    scene.vertices.push(vertex);
-                }
+    /// Draws `drawable`, with sprites showing the texture region `source` if it is set.
+    fn draw_frame(
+        &self,
+        transform: &Transform,
+        drawable: &Drawable,
+        source: Option<Rect>,
+        scene: &mut Scene,
+    ) {
+        let t = compute_transformation_matrix(transform);
+        let lod = drawable.lod(transform.scale.abs().max_element() * self.zoom);
+        let (vertices, indices) = match &lod {
+            Some(geometry) => (&geometry.vertices[..], &geometry.indices[..]),
+            None => (drawable.vertices(), drawable.indices()),
+        };
 
-                for i in polygon.indices() {
-                    scene.indices.push(index_offset + i);
-                }
+        let vertices = vertices.iter().map(|v| {
+            let mut position = (t * Vec4::from((v.position(), 0.0, 1.0))).to_array();
+            position[2] = transform.z_index;
+            let color = v.color().premultiplied().to_array();
+            let uv = match (drawable, source) {
+                (Drawable::Sprite(sprite), Some(source)) => sprite.frame_uv(v.uv(), source),
+                _ => v.uv(),
             }
-            Drawable::Rect(rect) => {
-                for v in rect.vertices() {
-                    let position = (t * Vec4::from((v.position(), 0.0, 1.0))).to_array();
-                    let color = v.color().to_array();
-                    let vertex = Vertex { position, color };
-                    scene.vertices.push(vertex);
-                }
+            .to_array();
+            Vertex {
+                position,
+                color,
+                uv,
+            }
+        });
 
-                for i in rect.indices() {
-                    scene.indices.push(index_offset + i);
+        let material = drawable.material().cloned();
+        let texture = drawable.texture().cloned();
+
+        if drawable.is_opaque() {
+            let index_offset = scene.vertices.len() as u32;
+            let first_index = scene.indices.len() as u32;
+            scene.vertices.extend(vertices);
+            scene
+                .indices
+                .extend(indices.iter().map(|&i| index_offset + u32::from(i)));
+            let last_index = scene.indices.len() as u32;
+            push_batch(
+                &mut scene.batches,
+                None,
+                material,
+                texture,
+                first_index..last_index,
+            );
+        } else {
+            scene.translucent.push(TranslucentShape {
+                z_index: transform.z_index,
+                blend_mode: drawable.blend_mode(),
+                material,
+                texture,
+                vertices: vertices.collect(),
+                indices: indices.iter().map(|&i| u32::from(i)).collect(),
+            });
+        }
+    }
+
+    /// Draws the particles of `emitter` at the transform's z-index. Particles are already in world
+    /// space, so the rest of the transform is ignored.
+    pub fn draw_particles(
+        &self,
+        transform: &Transform,
+        emitter: &ParticleEmitter,
+        scene: &mut Scene,
+    ) {
+        const CORNERS: [(Vec2, [f32; 2]); 4] = [
+            (Vec2::new(-0.5, -0.5), [0.0, 1.0]),
+            (Vec2::new(0.5, -0.5), [1.0, 1.0]),
+            (Vec2::new(0.5, 0.5), [1.0, 0.0]),
+            (Vec2::new(-0.5, 0.5), [0.0, 0.0]),
+        ];
+
+        let mut vertices = Vec::with_capacity(emitter.particle_count() * 4);
+        let mut indices = Vec::with_capacity(emitter.particle_count() * 6);
+        for (center, size, color) in emitter.quads() {
+            let first = vertices.len() as u32;
+            let color = color.premultiplied().to_array();
+            vertices.extend(CORNERS.iter().map(|&(corner, uv)| {
+                let position = center + corner * size;
+                Vertex {
+                    position: [position.x, position.y, transform.z_index, 1.0],
+                    color,
+                    uv,
                 }
-            }
+            }));
+            indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+        }
+
+        if indices.is_empty() {
+            return;
+        }
+
+        scene.translucent.push(TranslucentShape {
+            z_index: transform.z_index,
+            blend_mode: emitter.blend_mode,
+            material: None,
+            texture: emitter.texture.clone(),
+            vertices,
+            indices,
+        });
+    }
+
+    /// Lights the scene with `light`, positioned at the transform's translation. Drawing any light
+    /// darkens the rest of the scene to its ambient light, see [`Scene::set_ambient_light`].
+    pub fn draw_light(&self, transform: &Transform, light: &Light, scene: &mut Scene) {
+        scene.lights.push(SceneLight {
+            position: transform.translation,
+            light: *light,
+        });
+    }
+
+    /// Makes `drawable` cast shadows from lights with shadows enabled. The drawable itself is not
+    /// drawn.
+    pub fn draw_occluder(&self, transform: &Transform, drawable: &Drawable, scene: &mut Scene) {
+        if let Some(outline) = drawable.outline() {
+            let t = compute_transformation_matrix(transform);
+            let outline = outline
+                .into_iter()
+                .map(|p| (t * Vec4::from((p, 0.0, 1.0))).xy())
+                .collect();
+            scene.occluders.push(outline);
         }
     }
 }
@@ -161,24 +432,32 @@ pub trait Game {
     fn on_render(&self, _scene: &mut Scene, _ctx: &mut Context) {}
 }
 
-pub fn start<G>(mut window_config: WindowConfig, renderer_config: RendererConfig)
+/// Opens the window and runs the game until it exits. Returns an error if the window or the
+/// graphics device could not be created, or if the GPU fails while running.
+pub fn start<G>(
+    mut window_config: WindowConfig,
+    renderer_config: RendererConfig,
+) -> Result<(), PapercutError>
 where
     G: Game + Default + 'static,
 {
-    let event_loop = EventLoop::new();
+    let mut event_loop = EventLoop::new();
 
     let monitor = event_loop
         .available_monitors()
         .next()
-        .expect("no monitors found");
+        .ok_or(PapercutError::NoMonitor)?;
 
     let fullscreen = match window_config.fullscreen {
         Some(Fullscreen::Borderless) => {
             Some(winit::window::Fullscreen::Borderless(Some(monitor.clone())))
         }
         Some(Fullscreen::Exclusive) => {
-            let mode = monitor.video_modes().next().expect("no modes found");
-            Some(winit::window::Fullscreen::Exclusive(mode.clone()))
+            let mode = monitor
+                .video_modes()
+                .next()
+                .ok_or(PapercutError::NoVideoMode)?;
+            Some(winit::window::Fullscreen::Exclusive(mode))
         }
         _ => None,
     };
@@ -191,7 +470,7 @@ where
         ))
         .with_position(monitor.position())
         .with_visible(false);
-    let window = window_builder.build(&event_loop).unwrap();
+    let window = window_builder.build(&event_loop)?;
     window.set_fullscreen(fullscreen);
 
     {
@@ -199,18 +478,25 @@ where
         window_config.size = Vec2::new(size.width as f32, size.height as f32);
     }
 
-    let blend_state = wgpu::BlendState::ALPHA_BLENDING;
-
-    let sample_count = 4; // 1 = disable MSAA.
-
-    let mut device = block_on(GraphicsDevice::new(&window));
+    let mut device = block_on(GraphicsDevice::new(
+        &window,
+        renderer_config.backend,
+        renderer_config.power_preference,
+        renderer_config.color_format,
+    ))?;
+    let sample_count = device.supported_sample_count(renderer_config.sample_count);
     let mut renderer = Renderer::new(
         &device.device,
-        device.config.format,
-        blend_state,
+        &device.queue,
+        &device.config,
         sample_count,
         renderer_config.clear_color,
-    );
+    )?;
+    let mut light_renderer =
+        LightRenderer::new(&device.device, &renderer.globals_ubo, &device.config);
+    let mut post_processor =
+        PostProcessor::new(&device.device, &device.config, renderer_config.post_effects);
+    let mut frame_capture = FrameCapture::new(&device.device, &device.config);
     let mut camera = Camera::new(device.size.width as f32, device.size.height as f32);
 
     let mut input_helper = WinitInputHelper::new();
@@ -218,6 +504,13 @@ where
     let mut ctx = Context {
         window_title: window_config.title,
         window_size: window_config.size,
+        sample_count,
+        color_format: renderer_config.color_format,
+        render_settings_changed: false,
+        cameras: Vec::new(),
+        zoom: 1.0,
+        screenshots: Vec::new(),
+        capture: None,
     };
 
     let mut game = G::default();
@@ -238,7 +531,9 @@ where
     let mut update_count: u32 = 0;
     let mut ups = 0_u32;
 
-    event_loop.run(move |event, _, control_flow| {
+    let mut result = Ok(());
+
+    event_loop.run_return(|event, _, control_flow| {
         if new_frame {
             let this_frame = Instant::now();
             let mut frame_time = this_frame.saturating_duration_since(last_frame);
@@ -246,6 +541,9 @@ where
                 // If the frame rate dropped below 40 FPS, cap duration at 40 FPS.
                 frame_time = Duration::from_secs_f32(1.0 / 40.0);
             }
+            if let Some(capture) = &ctx.capture {
+                frame_time = capture.frame_time;
+            }
 
             last_frame = this_frame;
             accumulator += frame_time;
@@ -267,7 +565,13 @@ where
 
             device.resize(physical);
             renderer.resize(&device);
+            light_renderer.resize(&device);
+            post_processor.resize(&device);
+            frame_capture.resize(&device);
             camera.resize(physical.width as f32, physical.height as f32);
+            for camera in &mut ctx.cameras {
+                camera.resize(physical.width as f32, physical.height as f32);
+            }
         }
 
         //////////////////// UPDATE ////////////////////
@@ -285,26 +589,85 @@ where
 
         //////////////////// RENDER ////////////////////
         // TODO: Timing if not using vSync (which we are currently).
+        ctx.zoom = if ctx.cameras.is_empty() {
+            camera.zoom()
+        } else {
+            ctx.cameras.iter().map(Camera::zoom).fold(0.0, f32::max)
+        };
         game.on_render(&mut scene, &mut ctx);
 
-        let unaligned_indices_len = scene.indices.len();
-        for _ in 0..(unaligned_indices_len % COPY_BUFFER_ALIGNMENT as usize) {
-            scene.indices.push(0);
+        if std::mem::take(&mut ctx.render_settings_changed) {
+            if let Err(e) = device.set_color_format(ctx.color_format) {
+                log::error!("{}, exiting", e);
+                result = Err(e);
+                control_flow.set_exit();
+                return;
+            }
+            ctx.sample_count = device.supported_sample_count(ctx.sample_count);
+
+            renderer.set_surface_format(device.config.format);
+            renderer.set_sample_count(ctx.sample_count);
+            renderer.resize(&device);
+            light_renderer =
+                LightRenderer::new(&device.device, &renderer.globals_ubo, &device.config);
+            post_processor.set_format(&device.device, device.config.format);
+            post_processor.resize(&device);
+            // Finish writing the frames captured in the old format before replacing the capture.
+            frame_capture.poll(&device.device, true);
+            frame_capture = FrameCapture::new(&device.device, &device.config);
         }
 
-        let globals = Globals {
-            view: camera.get_view().to_cols_array_2d(),
-            projection: camera.get_projection().to_cols_array_2d(),
-        };
+        // The batches of all render targets follow the frame's, sharing its material parameters.
+        let mut batches = scene.build_batches();
+        let frame_batches = batches.len();
+        for offscreen in &mut scene.offscreen {
+            let first_batch = batches.len();
+            batches.extend(offscreen.scene.build_batches());
+            offscreen.batches = first_batch..batches.len();
+        }
 
-        device
-            .queue
-            .write_buffer(&renderer.globals_ubo, 0, bytemuck::cast_slice(&[globals]));
+        if let Err(e) = renderer.prepare_pipelines(&device.device, &batches) {
+            log::error!("{}, exiting", e);
+            result = Err(e);
+            control_flow.set_exit();
+            return;
+        }
+        renderer.prepare_textures(&device.device, &device.queue, &batches);
+        renderer.write_material_params(&device.device, &device.queue, &batches);
+
+        let cameras = if ctx.cameras.is_empty() {
+            std::slice::from_ref(&camera)
+        } else {
+            &ctx.cameras
+        };
+        let globals = cameras.iter().map(Globals::new).collect::<Vec<_>>();
+        renderer.write_globals(&device.queue, &globals);
+
+        // Empty viewports cannot be drawn to.
+        let views = cameras
+            .iter()
+            .enumerate()
+            .map(|(i, camera)| View {
+                globals_offset: renderer.globals_offset(i),
+                viewport: camera.viewport_in_pixels(),
+            })
+            .filter(|view| view.viewport.size.cmpge(Vec2::ONE).all())
+            .collect::<Vec<_>>();
 
         let frame = match device.surface.get_current_texture() {
             Ok(texture) => texture,
-            Err(e) => {
-                println!("swapchain error: {:?}", e);
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                device.reconfigure();
+                return;
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                log::error!("out of GPU memory, exiting");
+                result = Err(PapercutError::OutOfMemory);
+                control_flow.set_exit();
+                return;
+            }
+            Err(wgpu::SurfaceError::Timeout) => {
+                log::warn!("timed out acquiring the next frame");
                 return;
             }
         };
@@ -350,12 +713,34 @@ where
             0,
             &renderer.geometry_ibo,
             0,
-            (std::mem::size_of::<u16>() * scene.indices.len()) as BufferAddress,
+            (std::mem::size_of::<u32>() * scene.indices.len()) as BufferAddress,
         );
 
-        let render_target = frame
+        for offscreen in &scene.offscreen {
+            renderer.render_to_target(
+                &device,
+                &mut encoder,
+                &TargetPass {
+                    target: &offscreen.target,
+                    globals: offscreen.globals,
+                    clear_color: offscreen.clear_color,
+                    vertices: &offscreen.scene.vertices,
+                    indices: &offscreen.scene.indices,
+                    batches: &batches[offscreen.batches.clone()],
+                    first_batch: offscreen.batches.start,
+                },
+            );
+        }
+
+        let frame_view = frame
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
+        let captures = ctx.take_captures();
+        let output = match frame_capture.target() {
+            Some(target) if !captures.is_empty() => target,
+            _ => &frame_view,
+        };
+        let render_target = post_processor.scene_target().unwrap_or(output);
 
         let clear_color = wgpu::Color {
             r: renderer.clear_color.r as f64,
@@ -371,11 +756,11 @@ where
                     load: wgpu::LoadOp::Clear(clear_color),
                     store: true,
                 },
-                resolve_target: Some(&render_target),
+                resolve_target: Some(render_target),
             }
         } else {
             wgpu::RenderPassColorAttachment {
-                view: &render_target,
+                view: render_target,
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Clear(clear_color),
                     store: true,
@@ -389,7 +774,7 @@ where
                 label: None,
                 color_attachments: &[Some(color_attachment)],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: renderer.depth_texture_view.as_ref().unwrap(),
+                    view: &renderer.depth_texture_view,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(0.0),
                         store: true,
@@ -401,16 +786,37 @@ where
                 }),
             });
 
-            pass.set_pipeline(&renderer.geometry_pipeline);
-            pass.set_bind_group(0, &renderer.globals_bind_group, &[]);
-            pass.set_index_buffer(renderer.geometry_ibo.slice(..), wgpu::IndexFormat::Uint16);
+            pass.set_index_buffer(renderer.geometry_ibo.slice(..), wgpu::IndexFormat::Uint32);
             pass.set_vertex_buffer(0, renderer.geometry_vbo.slice(..));
 
-            pass.draw_indexed(0..unaligned_indices_len as u32, 0, 0..1);
+            for view in &views {
+                view.apply(&mut pass);
+                pass.set_bind_group(0, &renderer.globals_bind_group, &[view.globals_offset]);
+                renderer.draw_batches(&mut pass, &batches[..frame_batches], 0);
+            }
+        }
+
+        if let Some(ambient) = scene.ambient_light() {
+            light_renderer.render(
+                &device,
+                &mut encoder,
+                render_target,
+                &views,
+                &scene.lights,
+                &scene.occluders,
+                ambient,
+            );
+        }
+
+        post_processor.render(&device, &mut encoder, output);
+        if !captures.is_empty() {
+            frame_capture.capture(&device, &mut encoder, &frame_view, captures);
         }
 
         device.queue.submit(Some(encoder.finish()));
         frame.present();
+        frame_capture.after_submit();
+        frame_capture.poll(&device.device, false);
 
         frame_count += 1;
         let now = Instant::now();
@@ -428,19 +834,309 @@ where
 
         new_frame = true;
     });
+
+    frame_capture.poll(&device.device, true);
+
+    result
 }
 
 #[derive(Debug)]
 pub struct Scene {
     vertices: Vec<Vertex>,
-    indices: Vec<u16>,
+    indices: Vec<u32>,
+    batches: Vec<DrawBatch>,
+    translucent: Vec<TranslucentShape>,
+    lights: Vec<SceneLight>,
+    occluders: Vec<Vec<Vec2>>,
+    ambient_light: Option<Color>,
+    offscreen: Vec<OffscreenScene>,
 }
 
 impl Default for Scene {
     fn default() -> Self {
         let vertices = Vec::new();
         let indices = Vec::new();
+        let batches = Vec::new();
+        let translucent = Vec::new();
+        let lights = Vec::new();
+        let occluders = Vec::new();
+        let ambient_light = None;
+        let offscreen = Vec::new();
+
+        Self {
+            vertices,
+            indices,
+            batches,
+            translucent,
+            lights,
+            occluders,
+            ambient_light,
+            offscreen,
+        }
+    }
+}
+
+impl Scene {
+    /// Enables lighting, lighting the whole scene with `color` in addition to any drawn lights.
+    pub fn set_ambient_light(&mut self, color: Color) {
+        self.ambient_light = Some(color);
+    }
+
+    /// Renders `scene` into `target`, as seen by `camera`, before this scene is drawn. The target's
+    /// texture can then be drawn with a [`graphics::SpriteShape`], including in this scene.
+    /// Lights and post-processing effects only apply to the window, not to render targets. A scene
+    /// cannot draw the texture of the target it is rendered into.
+    pub fn render_to(
+        &mut self,
+        target: &RenderTarget,
+        camera: &Camera,
+        clear_color: Color,
+        mut scene: Scene,
+    ) {
+        // Targets rendered by the nested scene have to be ready before it is rendered.
+        self.offscreen.append(&mut scene.offscreen);
+
+        self.offscreen.push(OffscreenScene {
+            target: target.clone(),
+            globals: Globals::new(camera),
+            clear_color,
+            scene,
+            batches: 0..0,
+        });
+    }
+
+    /// The ambient light, if lighting is enabled. Lighting is enabled by setting an ambient light
+    /// or drawing a light, in which case the ambient light defaults to black.
+    fn ambient_light(&self) -> Option<Color> {
+        match self.ambient_light {
+            None if !self.lights.is_empty() => Some(Color::BLACK),
+            ambient_light => ambient_light,
+        }
+    }
+
+    /// Appends the translucent shapes, sorted back-to-front, after the opaque geometry and
+    /// returns the draw calls needed to render everything.
+    fn build_batches(&mut self) -> Vec<DrawBatch> {
+        let mut batches = std::mem::take(&mut self.batches);
+
+        // A stable sort keeps submission order for shapes sharing a z-index.
+        self.translucent
+            .sort_by(|a, b| a.z_index.total_cmp(&b.z_index));
+
+        for shape in self.translucent.drain(..) {
+            let index_offset = self.vertices.len() as u32;
+            let first_index = self.indices.len() as u32;
+            self.vertices.extend(shape.vertices);
+            self.indices
+                .extend(shape.indices.iter().map(|i| index_offset + i));
+            let last_index = self.indices.len() as u32;
+
+            push_batch(
+                &mut batches,
+                Some(shape.blend_mode),
+                shape.material,
+                shape.texture,
+                first_index..last_index,
+            );
+        }
+
+        batches
+    }
+}
+
+/// Extends the last batch if it draws with the same pipeline and material parameters.
+fn push_batch(
+    batches: &mut Vec<DrawBatch>,
+    blend_mode: Option<BlendMode>,
+    material: Option<MaterialInstance>,
+    texture: Option<Texture>,
+    indices: Range<u32>,
+) {
+    if let Some(batch) = batches.last_mut() {
+        let same_material = match (&batch.material, &material) {
+            (Some(a), Some(b)) => a.batches_with(b),
+            (None, None) => true,
+            _ => false,
+        };
+        let same_texture =
+            batch.texture.as_ref().map(Texture::id) == texture.as_ref().map(Texture::id);
+
+        if batch.blend_mode == blend_mode
+            && same_material
+            && same_texture
+            && batch.indices.end == indices.start
+        {
+            batch.indices.end = indices.end;
+            return;
+        }
+    }
+
+    batches.push(DrawBatch {
+        blend_mode,
+        material,
+        texture,
+        indices,
+    });
+}
+
+#[derive(Debug)]
+struct TranslucentShape {
+    z_index: f32,
+    blend_mode: BlendMode,
+    material: Option<MaterialInstance>,
+    texture: Option<Texture>,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+/// A scene to render into a [`RenderTarget`] before the frame, see [`Scene::render_to`].
+#[derive(Debug)]
+struct OffscreenScene {
+    target: RenderTarget,
+    globals: Globals,
+    clear_color: Color,
+    scene: Scene,
+    batches: Range<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        graphics::{Geometry, RectangleShape, Tessellator},
+        material::{Material, MaterialHandle},
+    };
+
+    fn context() -> Context {
+        Context {
+            window_title: String::new(),
+            window_size: Vec2::new(800.0, 600.0),
+            sample_count: 1,
+            color_format: ColorFormat::default(),
+            render_settings_changed: false,
+            cameras: Vec::new(),
+            zoom: 1.0,
+            screenshots: Vec::new(),
+            capture: None,
+        }
+    }
 
-        Self { vertices, indices }
+    fn square(color: Color) -> Drawable {
+        let mut rect = RectangleShape::default();
+        rect.size = Vec2::new(10.0, 10.0);
+        rect.fill = color.into();
+        rect.update(&mut Tessellator::new(0.1)).unwrap();
+        Drawable::Rect(rect)
+    }
+
+    fn transform(z_index: f32) -> Transform {
+        Transform {
+            z_index,
+            ..Default::default()
+        }
+    }
+
+    /// The z-index and color of the first vertex of every shape, in drawing order.
+    fn drawn(scene: &Scene) -> Vec<(f32, [f32; 4])> {
+        scene
+            .vertices
+            .chunks(4)
+            .map(|quad| (quad[0].position[2], quad[0].color))
+            .collect()
+    }
+
+    #[test]
+    fn translucent_shapes_are_drawn_back_to_front() {
+        let ctx = context();
+        let mut scene = Scene::default();
+        let glass = square(Color::new(1.0, 1.0, 1.0, 0.5));
+        for z_index in [2.0, 0.0, 1.0] {
+            ctx.draw_shape(&transform(z_index), &glass, &mut scene);
+        }
+
+        let batches = scene.build_batches();
+        let z_indices: Vec<f32> = drawn(&scene).iter().map(|(z, _)| *z).collect();
+        assert_eq!(z_indices, [0.0, 1.0, 2.0]);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].indices, 0..18);
+    }
+
+    #[test]
+    fn translucent_shapes_with_equal_z_keep_their_order() {
+        let ctx = context();
+        let mut scene = Scene::default();
+        let colors = [0.2, 0.4, 0.6].map(|red| Color::new(red, 0.0, 0.0, 0.5));
+        for color in colors {
+            ctx.draw_shape(&transform(1.0), &square(color), &mut scene);
+        }
+
+        scene.build_batches();
+        let drawn: Vec<[f32; 4]> = drawn(&scene).iter().map(|(_, color)| *color).collect();
+        let expected: Vec<[f32; 4]> = colors
+            .iter()
+            .map(|color| color.premultiplied().to_array())
+            .collect();
+        assert_eq!(drawn, expected);
+    }
+
+    #[test]
+    fn opaque_shapes_are_drawn_before_translucent_ones() {
+        let ctx = context();
+        let mut scene = Scene::default();
+        ctx.draw_shape(
+            &transform(0.0),
+            &square(Color::new(1.0, 1.0, 1.0, 0.5)),
+            &mut scene,
+        );
+        ctx.draw_shape(&transform(5.0), &square(Color::WHITE), &mut scene);
+        ctx.draw_shape(&transform(1.0), &square(Color::BLACK), &mut scene);
+
+        let batches = scene.build_batches();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].blend_mode, None);
+        assert_eq!(batches[0].indices, 0..12);
+        assert_eq!(batches[1].blend_mode, Some(BlendMode::Alpha));
+        assert_eq!(batches[1].indices, 12..18);
+        assert_eq!(drawn(&scene)[2].0, 0.0);
+    }
+
+    #[test]
+    fn batches_merge_only_when_everything_matches() {
+        let material = MaterialInstance::new(MaterialHandle::new(Material::new("flat", "")));
+        let mut tinted = material.clone();
+        tinted.params[0] = Vec4::ONE;
+        let texture = RenderTarget::new(1, 1).texture().clone();
+        let alpha = Some(BlendMode::Alpha);
+
+        let mut batches = Vec::new();
+        push_batch(&mut batches, alpha, None, None, 0..6);
+        push_batch(&mut batches, alpha, None, None, 6..12);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].indices, 0..12);
+
+        push_batch(&mut batches, Some(BlendMode::Additive), None, None, 12..18);
+        push_batch(&mut batches, Some(BlendMode::Additive), None, None, 24..30);
+        push_batch(&mut batches, alpha, Some(material.clone()), None, 30..36);
+        push_batch(&mut batches, alpha, Some(material.clone()), None, 36..42);
+        push_batch(&mut batches, alpha, Some(tinted), None, 42..48);
+        push_batch(&mut batches, alpha, Some(material), None, 48..54);
+        push_batch(&mut batches, alpha, None, Some(texture.clone()), 54..60);
+        push_batch(&mut batches, alpha, None, Some(texture), 60..66);
+        push_batch(&mut batches, alpha, None, None, 66..72);
+
+        let ranges: Vec<Range<u32>> = batches.iter().map(|b| b.indices.clone()).collect();
+        assert_eq!(
+            ranges,
+            [
+                0..12,
+                12..18,
+                24..30,
+                30..42,
+                42..48,
+                48..54,
+                54..66,
+                66..72
+            ]
+        );
     }
 }
@@ -0,0 +1,678 @@
+use std::ops::Range;
+
+use glam::Vec2;
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupLayout, Buffer, CommandEncoder, RenderPipeline, Sampler, TextureView,
+    VertexBufferLayout,
+};
+
+use crate::{
+    graphics::Color,
+    renderer::{Globals, GraphicsDevice, View},
+};
+
+#[derive(Debug, Copy, Clone)]
+pub enum LightKind {
+    /// Shines equally in every direction.
+    Point,
+    /// Shines in a cone around `direction`. Angles are measured in degrees from the direction to
+    /// the edge of the cone; light fades out between the inner and outer angle.
+    Spot {
+        direction: Vec2,
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub enum Shadows {
+    #[default]
+    None,
+    Hard,
+    /// Shadows with a penumbra, as cast by a light source with the given radius.
+    Soft {
+        source_radius: f32,
+    },
+}
+
+/// A light, positioned at the translation of the transform it is drawn with.
+#[derive(Debug, Copy, Clone)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Color,
+    pub intensity: f32,
+    /// Distance at which the light has faded out completely.
+    pub radius: f32,
+    /// Exponent of the attenuation curve: 1 fades linearly, higher values fade faster.
+    pub falloff: f32,
+    pub shadows: Shadows,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            kind: LightKind::Point,
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 100.0,
+            falloff: 2.0,
+            shadows: Shadows::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SceneLight {
+    pub position: Vec2,
+    pub light: Light,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct LightVertex {
+    position: [f32; 2],
+    center: [f32; 2],
+    color: [f32; 4],
+    params: [f32; 4],
+    direction: [f32; 2],
+}
+
+unsafe impl bytemuck::Pod for LightVertex {}
+unsafe impl bytemuck::Zeroable for LightVertex {}
+
+impl LightVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x4,
+        3 => Float32x4,
+        4 => Float32x2,
+    ];
+
+    fn desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<LightVertex>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Draws the lights into an accumulation buffer, cleared to the ambient light, which is then
+/// multiplied onto the rendered scene.
+///
+/// Shadows are drawn with the stencil buffer: for every shadowed light the edges of each occluder
+/// are extruded away from the light into the stencil buffer, and the light is only drawn where the
+/// stencil buffer is still clear. Soft shadows repeat this for several points across the light
+/// source, each contributing a share of the light's intensity.
+pub(crate) struct LightRenderer {
+    light_pipeline: RenderPipeline,
+    shadow_pipeline: RenderPipeline,
+    composite_pipeline: RenderPipeline,
+    globals_bind_group: BindGroup,
+    composite_bind_group_layout: BindGroupLayout,
+    sampler: Sampler,
+    targets: LightTargets,
+}
+
+struct LightTargets {
+    light_buffer: TextureView,
+    stencil_buffer: TextureView,
+    composite_bind_group: BindGroup,
+}
+
+struct LightPass {
+    shadows: Range<u32>,
+    light: Range<u32>,
+}
+
+impl LightRenderer {
+    const LIGHT_BUFFER_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+    const SOFT_SHADOW_SAMPLES: u32 = 8;
+    /// How far shadow volumes are extruded, relative to the light's radius.
+    const SHADOW_EXTRUSION: f32 = 100.0;
+
+    pub fn new(
+        device: &wgpu::Device,
+        globals_ubo: &Buffer,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> Self {
+        let surface_format = surface_config.format;
+        let globals_size = std::mem::size_of::<Globals>() as wgpu::BufferAddress;
+        let globals_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("lighting globals bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(globals_size),
+                    },
+                    count: None,
+                }],
+            });
+
+        let globals_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("lighting globals bind group"),
+            layout: &globals_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: globals_ubo,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(globals_size),
+                }),
+            }],
+        });
+
+        let composite_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("light composite bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("light buffer sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let lighting_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("lighting shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./../shaders/lighting.wgsl").into()),
+        });
+
+        let composite_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("light composite shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("./../shaders/light_composite.wgsl").into(),
+            ),
+        });
+
+        let lighting_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("lighting pipeline layout"),
+                bind_group_layouts: &[&globals_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let stencil_state = |compare, pass_op, write_mask| {
+            let face = wgpu::StencilFaceState {
+                compare,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op,
+            };
+
+            Some(wgpu::DepthStencilState {
+                format: Self::STENCIL_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState {
+                    front: face,
+                    back: face,
+                    read_mask: !0,
+                    write_mask,
+                },
+                bias: wgpu::DepthBiasState::default(),
+            })
+        };
+
+        let light_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("light pipeline"),
+            layout: Some(&lighting_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &lighting_module,
+                entry_point: "vs_light",
+                buffers: &[LightVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &lighting_module,
+                entry_point: "fs_light",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Self::LIGHT_BUFFER_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::REPLACE,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: stencil_state(
+                wgpu::CompareFunction::Equal,
+                wgpu::StencilOperation::Keep,
+                0,
+            ),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let shadow_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("shadow pipeline"),
+            layout: Some(&lighting_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &lighting_module,
+                entry_point: "vs_shadow",
+                buffers: &[VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 2]>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &lighting_module,
+                entry_point: "fs_shadow",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Self::LIGHT_BUFFER_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::empty(),
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: stencil_state(
+                wgpu::CompareFunction::Always,
+                wgpu::StencilOperation::Replace,
+                !0,
+            ),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let composite_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("light composite pipeline layout"),
+                bind_group_layouts: &[&composite_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let composite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("light composite pipeline"),
+            layout: Some(&composite_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &composite_module,
+                entry_point: "vs_composite",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &composite_module,
+                entry_point: "fs_composite",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    // Multiplies the scene by the accumulated light.
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Zero,
+                            dst_factor: wgpu::BlendFactor::Src,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Zero,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let targets = Self::create_targets(
+            device,
+            surface_config,
+            &composite_bind_group_layout,
+            &sampler,
+        );
+
+        Self {
+            light_pipeline,
+            shadow_pipeline,
+            composite_pipeline,
+            globals_bind_group,
+            composite_bind_group_layout,
+            sampler,
+            targets,
+        }
+    }
+
+    pub fn resize(&mut self, device: &GraphicsDevice) {
+        self.targets = Self::create_targets(
+            &device.device,
+            &device.config,
+            &self.composite_bind_group_layout,
+            &self.sampler,
+        );
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        composite_bind_group_layout: &BindGroupLayout,
+        sampler: &Sampler,
+    ) -> LightTargets {
+        let size = wgpu::Extent3d {
+            width: surface_config.width,
+            height: surface_config.height,
+            depth_or_array_layers: 1,
+        };
+
+        let light_buffer = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("light buffer"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: Self::LIGHT_BUFFER_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let stencil_buffer = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("light stencil buffer"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: Self::STENCIL_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let composite_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("light composite bind group"),
+            layout: composite_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&light_buffer),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        LightTargets {
+            light_buffer,
+            stencil_buffer,
+            composite_bind_group,
+        }
+    }
+
+    /// Accumulates `lights` into the light buffer and multiplies it onto `target`, which holds the
+    /// rendered scene. `occluders` are closed polygons in world coordinates. Lights are drawn once
+    /// for each of `views`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        device: &GraphicsDevice,
+        encoder: &mut CommandEncoder,
+        target: &TextureView,
+        views: &[View],
+        lights: &[SceneLight],
+        occluders: &[Vec<Vec2>],
+        ambient: Color,
+    ) {
+        let targets = &self.targets;
+
+        let mut light_vertices = Vec::new();
+        let mut shadow_vertices = Vec::new();
+
+        for light in lights
+            .iter()
+            .filter(|l| matches!(l.light.shadows, Shadows::None))
+        {
+            push_light_quad(&mut light_vertices, light, 1.0);
+        }
+        let unshadowed = 0..light_vertices.len() as u32;
+
+        let mut passes = Vec::new();
+        for light in lights {
+            let samples = match light.light.shadows {
+                Shadows::None => continue,
+                Shadows::Hard => vec![Vec2::ZERO],
+                Shadows::Soft { source_radius } => (0..Self::SOFT_SHADOW_SAMPLES)
+                    .map(|i| {
+                        let a =
+                            i as f32 / Self::SOFT_SHADOW_SAMPLES as f32 * 360.0_f32.to_radians();
+                        // Alternate between two rings to cover the light source more evenly.
+                        let r = if i % 2 == 0 {
+                            source_radius
+                        } else {
+                            source_radius * 0.5
+                        };
+                        Vec2::new(a.cos(), a.sin()) * r
+                    })
+                    .collect(),
+            };
+
+            let share = 1.0 / samples.len() as f32;
+            for offset in samples {
+                let first_shadow = shadow_vertices.len() as u32;
+                let origin = light.position + offset;
+                let extrusion = light.light.radius * Self::SHADOW_EXTRUSION;
+                for occluder in occluders {
+                    push_shadow_volume(&mut shadow_vertices, occluder, origin, extrusion);
+                }
+
+                let first_light = light_vertices.len() as u32;
+                push_light_quad(&mut light_vertices, light, share);
+
+                passes.push(LightPass {
+                    shadows: first_shadow..shadow_vertices.len() as u32,
+                    light: first_light..light_vertices.len() as u32,
+                });
+            }
+        }
+
+        let light_vbo = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("light vbo"),
+            contents: bytemuck::cast_slice(&light_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let shadow_vbo = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("shadow vbo"),
+            contents: bytemuck::cast_slice(&shadow_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        {
+            let mut pass = self.begin_light_pass(
+                encoder,
+                targets,
+                wgpu::LoadOp::Clear(wgpu::Color {
+                    r: ambient.r as f64,
+                    g: ambient.g as f64,
+                    b: ambient.b as f64,
+                    a: 1.0,
+                }),
+            );
+
+            if !unshadowed.is_empty() {
+                pass.set_pipeline(&self.light_pipeline);
+                pass.set_vertex_buffer(0, light_vbo.slice(..));
+                for view in views {
+                    view.apply(&mut pass);
+                    pass.set_bind_group(0, &self.globals_bind_group, &[view.globals_offset]);
+                    pass.draw(unshadowed.clone(), 0..1);
+                }
+            }
+        }
+
+        for light_pass in passes {
+            let mut pass = self.begin_light_pass(encoder, targets, wgpu::LoadOp::Load);
+
+            // The scissor keeps each view's shadows out of the stencil of the others.
+            for view in views {
+                view.apply(&mut pass);
+                pass.set_bind_group(0, &self.globals_bind_group, &[view.globals_offset]);
+
+                if !light_pass.shadows.is_empty() {
+                    pass.set_pipeline(&self.shadow_pipeline);
+                    pass.set_stencil_reference(1);
+                    pass.set_vertex_buffer(0, shadow_vbo.slice(..));
+                    pass.draw(light_pass.shadows.clone(), 0..1);
+                }
+
+                pass.set_pipeline(&self.light_pipeline);
+                pass.set_stencil_reference(0);
+                pass.set_vertex_buffer(0, light_vbo.slice(..));
+                pass.draw(light_pass.light.clone(), 0..1);
+            }
+        }
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("light composite pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        pass.set_pipeline(&self.composite_pipeline);
+        pass.set_bind_group(0, &targets.composite_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    fn begin_light_pass<'pass>(
+        &self,
+        encoder: &'pass mut CommandEncoder,
+        targets: &'pass LightTargets,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPass<'pass> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("light pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &targets.light_buffer,
+                resolve_target: None,
+                ops: wgpu::Operations { load, store: true },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &targets.stencil_buffer,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0.0),
+                    store: false,
+                }),
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: false,
+                }),
+            }),
+        })
+    }
+}
+
+/// A quad covering the area lit by `light`, scaled by `share` of its intensity.
+fn push_light_quad(vertices: &mut Vec<LightVertex>, light: &SceneLight, share: f32) {
+    let l = &light.light;
+    let intensity = l.intensity * share;
+    let color = [
+        l.color.r * intensity,
+        l.color.g * intensity,
+        l.color.b * intensity,
+        1.0,
+    ];
+
+    let (direction, cos_outer, cos_inner) = match l.kind {
+        LightKind::Point => (Vec2::ZERO, -1.0, -1.0),
+        LightKind::Spot {
+            direction,
+            inner_angle,
+            outer_angle,
+        } => (
+            direction.normalize_or_zero(),
+            outer_angle.to_radians().cos(),
+            inner_angle.to_radians().cos(),
+        ),
+    };
+
+    let center = light.position;
+    let corners = [
+        center + Vec2::new(-l.radius, -l.radius),
+        center + Vec2::new(l.radius, -l.radius),
+        center + Vec2::new(l.radius, l.radius),
+        center + Vec2::new(-l.radius, l.radius),
+    ];
+
+    for i in [0, 1, 2, 0, 2, 3] {
+        vertices.push(LightVertex {
+            position: corners[i].to_array(),
+            center: center.to_array(),
+            color,
+            params: [l.radius, l.falloff, cos_outer, cos_inner],
+            direction: direction.to_array(),
+        });
+    }
+}
+
+/// Extrudes the edges of `occluder` facing away from `origin`, covering the area behind it but
+/// leaving the occluder itself lit.
+fn push_shadow_volume(
+    vertices: &mut Vec<[f32; 2]>,
+    occluder: &[Vec2],
+    origin: Vec2,
+    extrusion: f32,
+) {
+    // Twice the signed area; positive for counter-clockwise polygons.
+    let winding = occluder
+        .iter()
+        .zip(occluder.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>()
+        .signum();
+
+    for (i, &a) in occluder.iter().enumerate() {
+        let b = occluder[(i + 1) % occluder.len()];
+        let outward_normal = -(b - a).perp() * winding;
+        if outward_normal.dot(a - origin) <= 0.0 {
+            continue;
+        }
+
+        let a_far = a + (a - origin).normalize_or_zero() * extrusion;
+        let b_far = b + (b - origin).normalize_or_zero() * extrusion;
+
+        for p in [a, b, b_far, a, b_far, a_far] {
+            vertices.push(p.to_array());
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Custom shaders for drawing shapes.
+//!
+//! A [`Material`] is a WGSL module providing a `vs_main` vertex entry point and an `fs_main`
+//! fragment entry point. The renderer binds the following resources, which the shader may declare
+//! as needed:
+//!
+//! ```wgsl
+//! struct Globals {
+//!     view: mat4x4<f32>,
+//!     projection: mat4x4<f32>,
+//! };
+//! @group(0) @binding(0) var<uniform> globals: Globals;
+//!
+//! struct MaterialParams {
+//!     params: array<vec4<f32>, 4>,
+//! };
+//! @group(1) @binding(0) var<uniform> material: MaterialParams;
+//!
+//! @group(2) @binding(0) var texture: texture_2d<f32>;
+//! @group(2) @binding(1) var texture_sampler: sampler;
+//! ```
+//!
+//! Each vertex provides its world position at `@location(0)` as a `vec4<f32>`, its color at
+//! `@location(1)` as a `vec4<f32>` with premultiplied alpha and its texture coordinates at
+//! `@location(2)` as a `vec2<f32>`. The fragment shader must write a premultiplied color to
+//! `@location(0)`. See `shaders/geometry.wgsl` for the default material.
+//!
+//! The renderer cannot know whether a shader writes translucent colors, so shapes drawn with a
+//! material always go through the translucent pass. They are sorted back-to-front with the other
+//! translucent shapes and do not write depth.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use glam::Vec4;
+
+/// Number of `vec4<f32>` user parameters available to a material.
+pub const MATERIAL_PARAM_COUNT: usize = 4;
+
+static NEXT_MATERIAL_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct MaterialId(u64);
+
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub label: String,
+    pub source: String,
+}
+
+impl Material {
+    pub fn new(label: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            source: source.into(),
+        }
+    }
+}
+
+/// A cheap to clone reference to a [`Material`]. The renderer compiles each material once, on
+/// first use, and caches its pipelines.
+#[derive(Debug, Clone)]
+pub struct MaterialHandle {
+    id: MaterialId,
+    material: Arc<Material>,
+}
+
+impl MaterialHandle {
+    pub fn new(material: Material) -> Self {
+        let id = MaterialId(NEXT_MATERIAL_ID.fetch_add(1, Ordering::Relaxed));
+
+        Self {
+            id,
+            material: Arc::new(material),
+        }
+    }
+
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    pub(crate) fn id(&self) -> MaterialId {
+        self.id
+    }
+}
+
+/// A material together with the parameters a drawable passes to it. Parameters can be changed
+/// every frame without re-tessellating the drawable.
+#[derive(Debug, Clone)]
+pub struct MaterialInstance {
+    pub material: MaterialHandle,
+    pub params: [Vec4; MATERIAL_PARAM_COUNT],
+}
+
+impl MaterialInstance {
+    pub fn new(material: MaterialHandle) -> Self {
+        Self {
+            material,
+            params: [Vec4::ZERO; MATERIAL_PARAM_COUNT],
+        }
+    }
+
+    /// Whether both instances can be drawn in the same draw call.
+    pub(crate) fn batches_with(&self, other: &Self) -> bool {
+        self.material.id == other.material.id && self.params == other.params
+    }
+}
@@ -0,0 +1,310 @@
+//! Particle effects simulated on the CPU, e.g. explosions, sparks and smoke.
+//!
+//! A [`ParticleEmitter`] component spawns particles at its entity's translation, continuously at
+//! [`ParticleEmitter::rate`] or all at once with [`ParticleEmitter::burst`]. Particles move in
+//! world space, so they stay behind when the emitter moves. [`update_particles`] simulates them
+//! in [`crate::Game::on_update`] and [`crate::Context::draw_particles`] draws all particles of
+//! an emitter as textured squares in a single batch.
+
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use glam::Vec2;
+use hecs::World;
+
+use crate::{
+    components::Transform,
+    graphics::{BlendMode, Color},
+    texture::Texture,
+};
+
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0x9e37_79b9_7f4a_7c15);
+
+#[derive(Debug, Clone)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+}
+
+#[derive(Debug)]
+pub struct ParticleEmitter {
+    /// Whether particles are spawned continuously at `rate`.
+    pub emitting: bool,
+    /// Particles spawned per second while emitting.
+    pub rate: f32,
+    /// Each particle lives for a random time in this range.
+    pub lifetime: Range<Duration>,
+    /// Each particle starts with a random speed in this range, in world units per second.
+    pub speed: Range<f32>,
+    /// Direction particles are emitted in, in degrees clockwise from up like
+    /// [`Transform::rotation`].
+    pub direction: f32,
+    /// Angle of the cone around `direction` that particles are emitted in, in degrees. 360 emits
+    /// in every direction.
+    pub spread: f32,
+    /// Acceleration of every particle, in world units per second squared.
+    pub gravity: Vec2,
+    /// Particle width in world units over their life, as `(age / lifetime, size)` keys in
+    /// ascending order that are interpolated linearly.
+    pub size_over_life: Vec<(f32, f32)>,
+    /// Particle color over their life, as `(age / lifetime, color)` keys like `size_over_life`.
+    pub color_over_life: Vec<(f32, Color)>,
+    /// Drawn over each particle, e.g. a soft dot. Particles without a texture are squares.
+    pub texture: Option<Texture>,
+    pub blend_mode: BlendMode,
+    /// Particles beyond this many are not spawned.
+    pub max_particles: usize,
+    particles: Vec<Particle>,
+    /// Particles to spawn at the next update, from bursts and fractions of `rate`.
+    pending: f32,
+    rng: u64,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            emitting: true,
+            rate: 50.0,
+            lifetime: Duration::from_secs(1)..Duration::from_secs(1),
+            speed: 50.0..100.0,
+            direction: 0.0,
+            spread: 360.0,
+            gravity: Vec2::ZERO,
+            size_over_life: vec![(0.0, 8.0), (1.0, 0.0)],
+            color_over_life: vec![(0.0, Color::WHITE), (1.0, Color::WHITE)],
+            texture: None,
+            blend_mode: BlendMode::Alpha,
+            max_particles: 10_000,
+            particles: Vec::new(),
+            pending: 0.0,
+            rng: next_seed(),
+        }
+    }
+}
+
+/// Clones get their own seed, so cloned emitters, e.g. spawned from a prefab, do not emit the
+/// same particles.
+impl Clone for ParticleEmitter {
+    fn clone(&self) -> Self {
+        Self {
+            emitting: self.emitting,
+            rate: self.rate,
+            lifetime: self.lifetime.clone(),
+            speed: self.speed.clone(),
+            direction: self.direction,
+            spread: self.spread,
+            gravity: self.gravity,
+            size_over_life: self.size_over_life.clone(),
+            color_over_life: self.color_over_life.clone(),
+            texture: self.texture.clone(),
+            blend_mode: self.blend_mode,
+            max_particles: self.max_particles,
+            particles: self.particles.clone(),
+            pending: self.pending,
+            rng: next_seed(),
+        }
+    }
+}
+
+impl ParticleEmitter {
+    /// Spawns `count` particles at once at the next update, whether or not the emitter is
+    /// emitting.
+    pub fn burst(&mut self, count: usize) {
+        self.pending += count as f32;
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Whether the emitter stopped emitting and all its particles died, e.g. to despawn a
+    /// finished explosion.
+    pub fn is_finished(&self) -> bool {
+        !self.emitting && self.pending < 1.0 && self.particles.is_empty()
+    }
+
+    /// Ages and moves the particles by `dt`, removes dead ones and spawns new ones at `origin`.
+    pub fn update(&mut self, origin: Vec2, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        for particle in &mut self.particles {
+            particle.age += dt;
+            particle.velocity += self.gravity * dt;
+            particle.position += particle.velocity * dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        if self.emitting {
+            self.pending += self.rate.max(0.0) * dt;
+        }
+        let count = self.pending.floor();
+        self.pending -= count;
+        let count = (count as usize).min(self.max_particles.saturating_sub(self.particles.len()));
+
+        self.particles.reserve(count);
+        for _ in 0..count {
+            let particle = self.spawn(origin);
+            self.particles.push(particle);
+        }
+    }
+
+    fn spawn(&mut self, origin: Vec2) -> Particle {
+        let angle = (self.direction + self.spread * (self.random() - 0.5)).to_radians();
+        let speed = lerp(self.speed.start, self.speed.end, self.random());
+        let lifetime = lerp(
+            self.lifetime.start.as_secs_f32(),
+            self.lifetime.end.as_secs_f32(),
+            self.random(),
+        );
+
+        Particle {
+            position: origin,
+            velocity: Vec2::new(angle.sin(), angle.cos()) * speed,
+            age: 0.0,
+            lifetime,
+        }
+    }
+
+    /// A random number in `0..1` from a xorshift generator; particles need speed, not quality.
+    fn random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Center, width and color of every living particle.
+    pub(crate) fn quads(&self) -> impl Iterator<Item = (Vec2, f32, Color)> + '_ {
+        self.particles.iter().map(|particle| {
+            let life = particle.age / particle.lifetime.max(f32::EPSILON);
+            let size = sample(&self.size_over_life, life, lerp).unwrap_or(0.0);
+            let color = sample(&self.color_over_life, life, |from, to, t| from.lerp(to, t))
+                .unwrap_or(Color::WHITE);
+            (particle.position, size, color)
+        })
+    }
+}
+
+fn next_seed() -> u64 {
+    NEXT_SEED.fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// The value of `keys` at `t`, holding the first and last key beyond their ends.
+fn sample<T: Copy>(keys: &[(f32, T)], t: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let (first, last) = (keys.first()?, keys.last()?);
+    if t <= first.0 {
+        return Some(first.1);
+    }
+
+    for pair in keys.windows(2) {
+        let ((from, from_value), (to, to_value)) = (pair[0], pair[1]);
+        if t <= to {
+            let range = (to - from).max(f32::EPSILON);
+            return Some(lerp(from_value, to_value, (t - from) / range));
+        }
+    }
+
+    Some(last.1)
+}
+
+/// Updates every entity's [`ParticleEmitter`], spawning particles at its transform's
+/// translation.
+pub fn update_particles(world: &mut World, dt: Duration) {
+    for (_id, (emitter, transform)) in world.query_mut::<(&mut ParticleEmitter, &Transform)>() {
+        emitter.update(transform.translation, dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emitters_spawn_at_their_rate_and_bursts() {
+        let mut emitter = ParticleEmitter {
+            rate: 100.0,
+            ..Default::default()
+        };
+        let dt = Duration::from_millis(10);
+
+        for _ in 0..50 {
+            emitter.update(Vec2::ZERO, dt);
+        }
+        assert_eq!(emitter.particle_count(), 50);
+
+        emitter.emitting = false;
+        emitter.burst(25);
+        emitter.update(Vec2::ZERO, dt);
+        assert_eq!(emitter.particle_count(), 75);
+
+        emitter.max_particles = 80;
+        emitter.burst(25);
+        emitter.update(Vec2::ZERO, dt);
+        assert_eq!(emitter.particle_count(), 80);
+        assert!(!emitter.is_finished());
+
+        emitter.update(Vec2::ZERO, Duration::from_secs(1));
+        assert!(emitter.is_finished());
+    }
+
+    #[test]
+    fn particles_follow_velocity_gravity_and_curves() {
+        let mut emitter = ParticleEmitter {
+            emitting: false,
+            speed: 100.0..100.0,
+            direction: 90.0,
+            spread: 0.0,
+            gravity: Vec2::new(0.0, -200.0),
+            lifetime: Duration::from_secs(2)..Duration::from_secs(2),
+            size_over_life: vec![(0.0, 10.0), (0.5, 20.0), (1.0, 0.0)],
+            color_over_life: vec![(0.0, Color::WHITE), (1.0, Color::TRANSPARENT)],
+            ..Default::default()
+        };
+
+        emitter.burst(1);
+        emitter.update(Vec2::new(5.0, 5.0), Duration::ZERO);
+        emitter.update(Vec2::new(5.0, 5.0), Duration::from_secs(1));
+
+        let (position, size, color) = emitter.quads().next().unwrap();
+        assert!(
+            position.abs_diff_eq(Vec2::new(105.0, -195.0), 1e-3),
+            "{}",
+            position
+        );
+        assert_eq!(size, 20.0);
+        assert!((color.a - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cloned_emitters_diverge() {
+        let emitter = ParticleEmitter {
+            emitting: false,
+            ..Default::default()
+        };
+        let mut a = emitter.clone();
+        let mut b = emitter.clone();
+        for clone in [&mut a, &mut b] {
+            clone.burst(10);
+            clone.update(Vec2::ZERO, Duration::ZERO);
+            clone.update(Vec2::ZERO, Duration::from_millis(100));
+        }
+
+        let positions = |emitter: &ParticleEmitter| {
+            emitter
+                .quads()
+                .map(|(position, _, _)| position)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(&a).len(), 10);
+        assert_ne!(positions(&a), positions(&b));
+    }
+}
@@ -0,0 +1,367 @@
+use std::{collections::HashMap, mem::Discriminant};
+
+use wgpu::{BindGroupLayout, Buffer, CommandEncoder, RenderPipeline, Sampler, TextureView};
+
+use crate::{graphics::Color, renderer::GraphicsDevice};
+
+/// A full-screen effect applied to the rendered scene. Effects configured on
+/// [`crate::RendererConfig`] run in order, each reading the output of the previous one.
+#[derive(Debug, Copy, Clone)]
+pub enum PostEffect {
+    /// Adds a glow around everything brighter than `threshold`.
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        /// Radius of the glow in pixels.
+        radius: f32,
+    },
+    /// Curved screen with scanlines.
+    Crt {
+        scanline_intensity: f32,
+        scanline_count: f32,
+        curvature: f32,
+    },
+    /// Darkens the edges of the screen.
+    Vignette { intensity: f32, smoothness: f32 },
+    /// Splits the red and blue channels towards the edges of the screen.
+    ChromaticAberration {
+        /// Offset in pixels at the edges of the screen.
+        offset: f32,
+    },
+    ColorGrading {
+        /// Exposure adjustment in stops.
+        exposure: f32,
+        contrast: f32,
+        saturation: f32,
+        tint: Color,
+    },
+}
+
+impl PostEffect {
+    fn label(&self) -> &'static str {
+        match self {
+            PostEffect::Bloom { .. } => "bloom",
+            PostEffect::Crt { .. } => "crt",
+            PostEffect::Vignette { .. } => "vignette",
+            PostEffect::ChromaticAberration { .. } => "chromatic aberration",
+            PostEffect::ColorGrading { .. } => "color grading",
+        }
+    }
+
+    fn shader(&self) -> &'static str {
+        match self {
+            PostEffect::Bloom { .. } => include_str!("./../shaders/post/bloom.wgsl"),
+            PostEffect::Crt { .. } => include_str!("./../shaders/post/crt.wgsl"),
+            PostEffect::Vignette { .. } => include_str!("./../shaders/post/vignette.wgsl"),
+            PostEffect::ChromaticAberration { .. } => {
+                include_str!("./../shaders/post/chromatic_aberration.wgsl")
+            }
+            PostEffect::ColorGrading { .. } => {
+                include_str!("./../shaders/post/color_grading.wgsl")
+            }
+        }
+    }
+
+    fn params(&self) -> [[f32; 4]; 2] {
+        match *self {
+            PostEffect::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => [[threshold, intensity, radius, 0.0], [0.0; 4]],
+            PostEffect::Crt {
+                scanline_intensity,
+                scanline_count,
+                curvature,
+            } => [
+                [scanline_intensity, scanline_count, curvature, 0.0],
+                [0.0; 4],
+            ],
+            PostEffect::Vignette {
+                intensity,
+                smoothness,
+            } => [[intensity, smoothness, 0.0, 0.0], [0.0; 4]],
+            PostEffect::ChromaticAberration { offset } => [[offset, 0.0, 0.0, 0.0], [0.0; 4]],
+            PostEffect::ColorGrading {
+                exposure,
+                contrast,
+                saturation,
+                tint,
+            } => [[exposure, contrast, saturation, 0.0], tint.to_array()],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct PostParams {
+    resolution: [f32; 4],
+    params: [[f32; 4]; 2],
+}
+
+unsafe impl bytemuck::Pod for PostParams {}
+unsafe impl bytemuck::Zeroable for PostParams {}
+
+/// Renders the scene into an intermediate texture and runs the configured effects over it, the
+/// last of which writes to the frame.
+pub(crate) struct PostProcessor {
+    effects: Vec<PostEffect>,
+    format: wgpu::TextureFormat,
+    bind_group_layout: BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: Sampler,
+    pipelines: HashMap<Discriminant<PostEffect>, RenderPipeline>,
+    params_ubos: Vec<Buffer>,
+    targets: [TextureView; 2],
+}
+
+impl PostProcessor {
+    pub fn new(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        effects: Vec<PostEffect>,
+    ) -> Self {
+        let format = surface_config.format;
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("post processing bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<PostParams>() as wgpu::BufferAddress
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("post processing pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("post processing sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let mut post_processor = Self {
+            effects: Vec::new(),
+            format,
+            bind_group_layout,
+            pipeline_layout,
+            sampler,
+            pipelines: HashMap::new(),
+            params_ubos: Vec::new(),
+            targets: Self::create_targets(device, surface_config, format),
+        };
+        post_processor.set_effects(device, effects);
+
+        post_processor
+    }
+
+    pub fn set_effects(&mut self, device: &wgpu::Device, effects: Vec<PostEffect>) {
+        for effect in &effects {
+            let key = std::mem::discriminant(effect);
+            if !self.pipelines.contains_key(&key) {
+                let pipeline = self.create_pipeline(device, effect);
+                self.pipelines.insert(key, pipeline);
+            }
+        }
+
+        while self.params_ubos.len() < effects.len() {
+            self.params_ubos
+                .push(device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("post processing params ubo"),
+                    size: std::mem::size_of::<PostParams>() as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }));
+        }
+
+        self.effects = effects;
+    }
+
+    /// Rebuilds the effect pipelines for a new surface format. Call [`PostProcessor::resize`]
+    /// afterwards to recreate the intermediate targets.
+    pub fn set_format(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat) {
+        if format != self.format {
+            self.format = format;
+            self.pipelines.clear();
+            let effects = std::mem::take(&mut self.effects);
+            self.set_effects(device, effects);
+        }
+    }
+
+    fn create_pipeline(&self, device: &wgpu::Device, effect: &PostEffect) -> RenderPipeline {
+        let source = format!(
+            "{}{}",
+            include_str!("./../shaders/post/fullscreen.wgsl"),
+            effect.shader()
+        );
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(effect.label()),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(effect.label()),
+            layout: Some(&self.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: self.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+
+    pub fn resize(&mut self, device: &GraphicsDevice) {
+        self.targets = Self::create_targets(&device.device, &device.config, self.format);
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> [TextureView; 2] {
+        let create_target = || {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("post processing target"),
+                    size: wgpu::Extent3d {
+                        width: surface_config.width,
+                        height: surface_config.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        [create_target(), create_target()]
+    }
+
+    /// Where the scene should be rendered, or `None` to render straight into the frame because no
+    /// effects are configured.
+    pub fn scene_target(&self) -> Option<&TextureView> {
+        if self.effects.is_empty() {
+            None
+        } else {
+            Some(&self.targets[0])
+        }
+    }
+
+    pub fn render(
+        &self,
+        device: &GraphicsDevice,
+        encoder: &mut CommandEncoder,
+        frame: &TextureView,
+    ) {
+        if self.effects.is_empty() {
+            return;
+        }
+        let targets = &self.targets;
+
+        let width = device.config.width as f32;
+        let height = device.config.height as f32;
+        let resolution = [width, height, 1.0 / width, 1.0 / height];
+
+        for (i, effect) in self.effects.iter().enumerate() {
+            let params = PostParams {
+                resolution,
+                params: effect.params(),
+            };
+            device
+                .queue
+                .write_buffer(&self.params_ubos[i], 0, bytemuck::cast_slice(&[params]));
+
+            let source = &targets[i % 2];
+            let destination = if i + 1 == self.effects.len() {
+                frame
+            } else {
+                &targets[(i + 1) % 2]
+            };
+
+            let bind_group = device.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("post processing bind group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.params_ubos[i].as_entire_binding(),
+                    },
+                ],
+            });
+
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(effect.label()),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: destination,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            pass.set_pipeline(&self.pipelines[&std::mem::discriminant(effect)]);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+}
@@ -1,16 +1,35 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    sync::Weak,
+};
+
+use futures::executor::block_on;
+use glam::Vec4;
 use wgpu::{
-    BindGroup, Buffer, BufferAddress, BufferUsages, Device, RenderPipeline, TextureView,
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupLayout, Buffer, BufferAddress, BufferUsages, CommandEncoder, Device,
+    PipelineLayout, RenderPass, RenderPipeline, Sampler, ShaderModule, TextureView,
     VertexBufferLayout,
 };
 use winit::window::Window;
 
-use crate::graphics::Color;
+use crate::{
+    camera::{Camera, MAX_CAMERAS},
+    graphics::{BlendMode, Color, Rect},
+    material::{MaterialId, MaterialInstance, MATERIAL_PARAM_COUNT},
+    texture::{RenderTarget, Texture, TextureId},
+    Backend, ColorFormat, PapercutError, PowerPreference,
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct Vertex {
     pub position: [f32; 4],
     pub color: [f32; 4],
+    pub uv: [f32; 2],
 }
 
 unsafe impl bytemuck::Pod for Vertex {}
@@ -32,6 +51,11 @@ impl Vertex {
                     format: wgpu::VertexFormat::Float32x4,
                     shader_location: 1,
                 },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    format: wgpu::VertexFormat::Float32x2,
+                    shader_location: 2,
+                },
             ],
         }
     }
@@ -44,33 +68,113 @@ pub struct Globals {
     pub projection: [[f32; 4]; 4],
 }
 
+impl Globals {
+    pub fn new(camera: &Camera) -> Self {
+        Self {
+            view: camera.get_view().to_cols_array_2d(),
+            projection: camera.get_projection().to_cols_array_2d(),
+        }
+    }
+}
+
 unsafe impl bytemuck::Pod for Globals {}
 unsafe impl bytemuck::Zeroable for Globals {}
 
+/// A run of indices drawn with the same pipeline, material parameters and texture. Opaque
+/// geometry has no blend mode and geometry without a material uses the default geometry shader.
+#[derive(Debug)]
+pub struct DrawBatch {
+    pub blend_mode: Option<BlendMode>,
+    pub material: Option<MaterialInstance>,
+    pub texture: Option<Texture>,
+    pub indices: Range<u32>,
+}
+
+impl DrawBatch {
+    fn material_id(&self) -> Option<MaterialId> {
+        self.material.as_ref().map(|m| m.material.id())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct PipelineKey {
+    material: Option<MaterialId>,
+    blend_mode: Option<BlendMode>,
+    sample_count: u32,
+}
+
+/// Blend states expect colors with premultiplied alpha.
+fn blend_state(blend_mode: BlendMode) -> wgpu::BlendState {
+    let keep_destination_alpha = wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    };
+
+    match blend_mode {
+        BlendMode::Alpha => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+        BlendMode::Additive => wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: keep_destination_alpha,
+        },
+        BlendMode::Multiply => wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Dst,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: keep_destination_alpha,
+        },
+    }
+}
+
 pub struct GraphicsDevice {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub surface: wgpu::Surface,
     pub config: wgpu::SurfaceConfiguration,
     pub size: winit::dpi::PhysicalSize<u32>,
+    adapter: wgpu::Adapter,
 }
 
 impl GraphicsDevice {
-    pub async fn new(window: &Window) -> Self {
+    pub async fn new(
+        window: &Window,
+        backend: Backend,
+        power_preference: PowerPreference,
+        color_format: ColorFormat,
+    ) -> Result<Self, PapercutError> {
         let size = window.inner_size();
 
-        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let backends = match backend {
+            Backend::Auto => wgpu::Backends::all(),
+            Backend::Vulkan => wgpu::Backends::VULKAN,
+            Backend::Metal => wgpu::Backends::METAL,
+            Backend::Dx12 => wgpu::Backends::DX12,
+            Backend::Dx11 => wgpu::Backends::DX11,
+            Backend::Gl => wgpu::Backends::GL,
+        };
+        let instance = wgpu::Instance::new(backends);
+
+        let surface = unsafe { instance.create_surface(window) };
 
-        let surface = unsafe { instance.create_surface(&window) };
+        let power_preference = match power_preference {
+            PowerPreference::LowPower => wgpu::PowerPreference::LowPower,
+            PowerPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        };
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::LowPower,
+                power_preference,
                 compatible_surface: Some(&surface),
                 force_fallback_adapter: false,
             })
             .await
-            .unwrap();
+            .ok_or(PapercutError::NoAdapter)?;
 
         let (device, queue) = adapter
             .request_device(
@@ -81,12 +185,11 @@ impl GraphicsDevice {
                 },
                 None,
             )
-            .await
-            .unwrap();
+            .await?;
 
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            format: Self::choose_format(&surface, &adapter, color_format)?,
             width: size.width,
             height: size.height,
             present_mode: wgpu::PresentMode::AutoVsync,
@@ -94,13 +197,66 @@ impl GraphicsDevice {
 
         surface.configure(&device, &config);
 
-        Self {
+        Ok(Self {
             device,
             queue,
             surface,
             config,
             size,
+            adapter,
+        })
+    }
+
+    /// Picks the first surface format matching `color_format`, falling back to the surface's
+    /// preferred format.
+    fn choose_format(
+        surface: &wgpu::Surface,
+        adapter: &wgpu::Adapter,
+        color_format: ColorFormat,
+    ) -> Result<wgpu::TextureFormat, PapercutError> {
+        let formats = surface.get_supported_formats(adapter);
+        formats
+            .iter()
+            .copied()
+            .find(|format| format.describe().srgb == (color_format == ColorFormat::Srgb))
+            .or_else(|| formats.first().copied())
+            .ok_or(PapercutError::NoSurfaceFormat)
+    }
+
+    pub fn set_color_format(&mut self, color_format: ColorFormat) -> Result<(), PapercutError> {
+        let format = Self::choose_format(&self.surface, &self.adapter, color_format)?;
+        if format != self.config.format {
+            self.config.format = format;
+            self.surface.configure(&self.device, &self.config);
         }
+        Ok(())
+    }
+
+    /// Returns the closest sample count to `requested` that the adapter can render with. WebGPU
+    /// only guarantees 1 and 4 samples, so other counts are rounded down to one of those.
+    pub fn supported_sample_count(&self, requested: u32) -> u32 {
+        let multisample = [self.config.format, DEPTH_FORMAT].iter().all(|format| {
+            self.adapter
+                .get_texture_format_features(*format)
+                .flags
+                .contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE)
+        });
+
+        let supported = if requested >= 4 && multisample { 4 } else { 1 };
+        if supported != requested {
+            log::warn!(
+                "{}x MSAA is not supported, using {}x instead",
+                requested,
+                supported
+            );
+        }
+
+        supported
+    }
+
+    /// Configures the surface again after it was lost or became outdated.
+    pub fn reconfigure(&mut self) {
+        self.surface.configure(&self.device, &self.config);
     }
 
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
@@ -114,17 +270,87 @@ impl GraphicsDevice {
     }
 }
 
+const MATERIAL_PARAMS_SIZE: wgpu::BufferAddress =
+    (std::mem::size_of::<[f32; 4]>() * MATERIAL_PARAM_COUNT) as wgpu::BufferAddress;
+
+/// A part of the frame drawn by one camera.
+pub struct View {
+    pub globals_offset: wgpu::DynamicOffset,
+    /// Viewport in pixels from the top left of the frame.
+    pub viewport: Rect,
+}
+
+impl View {
+    /// Restricts drawing in `pass` to the view.
+    pub fn apply(&self, pass: &mut RenderPass) {
+        let Rect { position, size } = self.viewport;
+        pass.set_viewport(position.x, position.y, size.x, size.y, 0.0, 1.0);
+        pass.set_scissor_rect(
+            position.x as u32,
+            position.y as u32,
+            size.x as u32,
+            size.y as u32,
+        );
+    }
+}
+
+/// A scene to render into a [`RenderTarget`], see [`Renderer::render_to_target`].
+pub struct TargetPass<'a> {
+    pub target: &'a RenderTarget,
+    pub globals: Globals,
+    pub clear_color: Color,
+    pub vertices: &'a [Vertex],
+    pub indices: &'a [u32],
+    pub batches: &'a [DrawBatch],
+    /// Index of the first batch within the batches passed to [`Renderer::write_material_params`].
+    pub first_batch: usize,
+}
+
+struct GpuTexture {
+    view: TextureView,
+    bind_group: BindGroup,
+    /// Dead once every handle to the texture has been dropped.
+    liveness: Weak<()>,
+}
+
+/// What rendering into a [`RenderTarget`] needs besides its texture. Each target has its own
+/// globals since every target is rendered with a different camera within the same submission.
+struct RenderTargetResources {
+    sample_count: u32,
+    multisampled_render_target: Option<TextureView>,
+    depth_texture_view: TextureView,
+    globals_ubo: Buffer,
+    globals_bind_group: BindGroup,
+}
+
 pub struct Renderer {
     pub max_geometry_vertices: usize,
     pub max_geometry_indices: usize,
     pub clear_color: Color,
     pub globals_ubo: Buffer,
-    pub geometry_pipeline: RenderPipeline,
     pub globals_bind_group: BindGroup,
+    globals_bind_group_layout: BindGroupLayout,
+    globals_stride: u64,
+    pub material_ubo: Buffer,
+    pub material_bind_group: BindGroup,
+    material_bind_group_layout: BindGroupLayout,
+    material_params_stride: u64,
+    max_material_params: usize,
+    texture_bind_group_layout: BindGroupLayout,
+    texture_sampler: Sampler,
+    white_texture_bind_group: BindGroup,
+    textures: HashMap<TextureId, GpuTexture>,
+    render_targets: HashMap<TextureId, RenderTargetResources>,
+    surface_format: wgpu::TextureFormat,
+    geometry_pipeline_layout: PipelineLayout,
+    geometry_module: ShaderModule,
+    material_modules: HashMap<MaterialId, ShaderModule>,
+    failed_materials: HashSet<MaterialId>,
+    pipelines: HashMap<PipelineKey, RenderPipeline>,
     pub geometry_ibo: Buffer,
     pub geometry_vbo: Buffer,
     pub multisampled_render_target: Option<TextureView>,
-    pub depth_texture_view: Option<TextureView>,
+    pub depth_texture_view: TextureView,
     pub msaa_sample_count: u32,
 }
 
@@ -133,19 +359,22 @@ impl Renderer {
 
     pub fn new(
         device: &wgpu::Device,
-        surface_format: wgpu::TextureFormat,
-        blend_state: wgpu::BlendState,
+        queue: &wgpu::Queue,
+        surface_config: &wgpu::SurfaceConfiguration,
         msaa_sample_count: u32,
         clear_color: Color,
-    ) -> Self {
+    ) -> Result<Self, PapercutError> {
+        let surface_format = surface_config.format;
         let max_geometry_vertices = 4 * Self::INITIAL_GEOMETRY_COUNT;
         let max_geometry_indices = 6 * Self::INITIAL_GEOMETRY_COUNT;
 
         let globals_byte_buffer_size = std::mem::size_of::<Globals>() as wgpu::BufferAddress;
+        let globals_stride = globals_byte_buffer_size
+            .max(device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress);
 
         let globals_ubo = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("globals ubo"),
-            size: globals_byte_buffer_size,
+            size: globals_stride * MAX_CAMERAS as wgpu::BufferAddress,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -159,18 +388,13 @@ impl Renderer {
 
         let geometry_ibo = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("geometry ibo"),
-            size: (std::mem::size_of::<u16>() * max_geometry_indices) as BufferAddress,
+            size: (std::mem::size_of::<u32>() * max_geometry_indices) as BufferAddress,
             usage: wgpu::BufferUsages::INDEX | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
 
-        let geometry_vs_module = &device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("geometry vs"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("./../shaders/geometry.wgsl").into()),
-        });
-
-        let geometry_fs_module = &device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("geometry fs"),
+        let geometry_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("geometry shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("./../shaders/geometry.wgsl").into()),
         });
 
@@ -179,10 +403,10 @@ impl Renderer {
                 label: Some("bind group layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
+                        has_dynamic_offset: true,
                         min_binding_size: wgpu::BufferSize::new(globals_byte_buffer_size),
                     },
                     count: None,
@@ -194,20 +418,180 @@ impl Renderer {
             layout: &globals_bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: wgpu::BindingResource::Buffer(globals_ubo.as_entire_buffer_binding()),
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &globals_ubo,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(globals_byte_buffer_size),
+                }),
             }],
         });
 
+        let material_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("material bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(MATERIAL_PARAMS_SIZE),
+                    },
+                    count: None,
+                }],
+            });
+
+        let material_params_stride = MATERIAL_PARAMS_SIZE
+            .max(device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress);
+        let max_material_params = Self::INITIAL_GEOMETRY_COUNT;
+        let (material_ubo, material_bind_group) = Self::create_material_params_buffer(
+            device,
+            &material_bind_group_layout,
+            material_params_stride,
+            max_material_params,
+        );
+
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("texture bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("texture sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let white_texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("white texture"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            },
+            &[255; 4],
+        );
+        let white_texture_bind_group = Self::create_texture_bind_group(
+            device,
+            &texture_bind_group_layout,
+            &texture_sampler,
+            &white_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+        );
+
         let geometry_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                bind_group_layouts: &[&globals_bind_group_layout],
+                bind_group_layouts: &[
+                    &globals_bind_group_layout,
+                    &material_bind_group_layout,
+                    &texture_bind_group_layout,
+                ],
                 push_constant_ranges: &[],
                 label: Some("geometry pipeline layout"),
             });
 
-        let geometry_depth_stencil_state = Some(wgpu::DepthStencilState {
-            format: wgpu::TextureFormat::Depth32Float,
-            depth_write_enabled: true,
+        let material_modules = HashMap::new();
+        let failed_materials = HashSet::new();
+        let pipelines = HashMap::new();
+
+        let multisampled_render_target = (msaa_sample_count > 1).then(|| {
+            Self::create_multisampled_framebuffer(device, surface_config, msaa_sample_count)
+        });
+
+        let depth_texture_view =
+            Self::create_depth_texture_view(device, surface_config, msaa_sample_count);
+
+        let mut renderer = Self {
+            max_geometry_vertices,
+            max_geometry_indices,
+            clear_color,
+            globals_ubo,
+            globals_bind_group,
+            globals_bind_group_layout,
+            globals_stride,
+            material_ubo,
+            material_bind_group,
+            material_bind_group_layout,
+            material_params_stride,
+            max_material_params,
+            texture_bind_group_layout,
+            texture_sampler,
+            white_texture_bind_group,
+            textures: HashMap::new(),
+            render_targets: HashMap::new(),
+            surface_format,
+            geometry_pipeline_layout,
+            geometry_module,
+            material_modules,
+            failed_materials,
+            pipelines,
+            geometry_ibo,
+            geometry_vbo,
+            multisampled_render_target,
+            depth_texture_view,
+            msaa_sample_count,
+        };
+
+        // Fails early if the built-in shader does not validate instead of on the first frame.
+        renderer.build_pipeline(
+            device,
+            PipelineKey {
+                material: None,
+                blend_mode: None,
+                sample_count: msaa_sample_count,
+            },
+        )?;
+
+        Ok(renderer)
+    }
+
+    /// Opaque geometry (`blend_mode` of `None`) writes depth and culls back faces. Translucent
+    /// geometry is expected to be sorted back-to-front so it neither writes depth nor culls.
+    fn create_geometry_pipeline(
+        device: &wgpu::Device,
+        label: &str,
+        layout: &wgpu::PipelineLayout,
+        module: &wgpu::ShaderModule,
+        surface_format: wgpu::TextureFormat,
+        blend_mode: Option<BlendMode>,
+        msaa_sample_count: u32,
+    ) -> RenderPipeline {
+        let (blend, depth_write_enabled, cull_mode) = match blend_mode {
+            None => (wgpu::BlendState::REPLACE, true, Some(wgpu::Face::Back)),
+            Some(blend_mode) => (blend_state(blend_mode), false, None),
+        };
+
+        let depth_stencil_state = wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled,
             depth_compare: wgpu::CompareFunction::GreaterEqual,
             stencil: wgpu::StencilState {
                 front: wgpu::StencilFaceState::IGNORE,
@@ -216,22 +600,22 @@ impl Renderer {
                 write_mask: 0,
             },
             bias: wgpu::DepthBiasState::default(),
-        });
+        };
 
-        let geometry_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("geometry pipeline"),
-            layout: Some(&geometry_pipeline_layout),
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
             vertex: wgpu::VertexState {
-                module: &geometry_vs_module,
+                module,
                 entry_point: "vs_main",
                 buffers: &[Vertex::desc()],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &geometry_fs_module,
+                module,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_format,
-                    blend: Some(blend_state),
+                    blend: Some(blend),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
@@ -240,65 +624,538 @@ impl Renderer {
                 polygon_mode: wgpu::PolygonMode::Fill,
                 front_face: wgpu::FrontFace::Ccw,
                 strip_index_format: None,
-                cull_mode: Some(wgpu::Face::Back),
+                cull_mode,
                 conservative: false,
                 unclipped_depth: false,
             },
-            depth_stencil: geometry_depth_stencil_state.clone(),
+            depth_stencil: Some(depth_stencil_state),
             multisample: wgpu::MultisampleState {
                 count: msaa_sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+        })
+    }
+
+    fn pipeline_key(&self, batch: &DrawBatch) -> PipelineKey {
+        let material = batch
+            .material_id()
+            .filter(|id| !self.failed_materials.contains(id));
+
+        PipelineKey {
+            material,
+            blend_mode: batch.blend_mode,
+            sample_count: self.msaa_sample_count,
+        }
+    }
+
+    /// Compiles the shaders and pipelines needed by `batches` which are not cached yet. Materials
+    /// which fail to compile are reported once and drawn with the default geometry shader.
+    pub fn prepare_pipelines(
+        &mut self,
+        device: &Device,
+        batches: &[DrawBatch],
+    ) -> Result<(), PapercutError> {
+        for batch in batches {
+            if let Some(instance) = &batch.material {
+                self.compile_material(device, instance);
+            }
+
+            if !self.build_pipeline(device, self.pipeline_key(batch))? {
+                // The material has now been marked as failed, so this is the default pipeline.
+                self.build_pipeline(device, self.pipeline_key(batch))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_material(&mut self, device: &Device, instance: &MaterialInstance) {
+        let id = instance.material.id();
+        if self.material_modules.contains_key(&id) || self.failed_materials.contains(&id) {
+            return;
+        }
+
+        let material = instance.material.material();
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&material.label),
+            source: wgpu::ShaderSource::Wgsl(material.source.as_str().into()),
         });
 
-        let multisampled_render_target = None;
+        match block_on(device.pop_error_scope()) {
+            None => {
+                self.material_modules.insert(id, module);
+            }
+            Some(e) => {
+                log::error!("material '{}' failed to compile: {}", material.label, e);
+                self.failed_materials.insert(id);
+            }
+        }
+    }
 
-        let depth_texture_view = None;
+    /// Returns `false` if the pipeline uses a material that turned out to be invalid. Fails if a
+    /// pipeline of the built-in geometry shader is invalid.
+    fn build_pipeline(&mut self, device: &Device, key: PipelineKey) -> Result<bool, PapercutError> {
+        if self.pipelines.contains_key(&key) {
+            return Ok(true);
+        }
 
-        Self {
-            max_geometry_vertices,
-            max_geometry_indices,
-            clear_color,
-            globals_ubo,
-            geometry_pipeline,
-            globals_bind_group,
-            geometry_ibo,
-            geometry_vbo,
-            multisampled_render_target,
-            depth_texture_view,
-            msaa_sample_count,
+        let module = match key.material {
+            Some(id) => &self.material_modules[&id],
+            None => &self.geometry_module,
+        };
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = Self::create_geometry_pipeline(
+            device,
+            "geometry pipeline",
+            &self.geometry_pipeline_layout,
+            module,
+            self.surface_format,
+            key.blend_mode,
+            key.sample_count,
+        );
+
+        match (block_on(device.pop_error_scope()), key.material) {
+            (None, _) => {
+                self.pipelines.insert(key, pipeline);
+                Ok(true)
+            }
+            (Some(e), Some(id)) => {
+                log::error!("material pipeline failed to build: {}", e);
+                self.material_modules.remove(&id);
+                self.failed_materials.insert(id);
+                Ok(false)
+            }
+            (Some(e), None) => Err(PapercutError::PipelineCreation(e)),
         }
     }
 
+    /// The pipeline for a batch, which must have been passed to [`Self::prepare_pipelines`].
+    pub fn pipeline(&self, batch: &DrawBatch) -> &RenderPipeline {
+        &self.pipelines[&self.pipeline_key(batch)]
+    }
+
+    /// Uploads the material parameters of each batch. The parameters of batch `i` live at
+    /// [`Self::material_params_offset`]`(i)`.
+    pub fn write_material_params(
+        &mut self,
+        device: &Device,
+        queue: &wgpu::Queue,
+        batches: &[DrawBatch],
+    ) {
+        let count = batches.len().max(1);
+        if count > self.max_material_params {
+            let (material_ubo, material_bind_group) = Self::create_material_params_buffer(
+                device,
+                &self.material_bind_group_layout,
+                self.material_params_stride,
+                count,
+            );
+            self.material_ubo = material_ubo;
+            self.material_bind_group = material_bind_group;
+            self.max_material_params = count;
+        }
+
+        let stride = self.material_params_stride as usize;
+        let mut data = vec![0_u8; stride * count];
+        for (i, batch) in batches.iter().enumerate() {
+            let params = batch
+                .material
+                .as_ref()
+                .map_or([Vec4::ZERO; MATERIAL_PARAM_COUNT], |m| m.params)
+                .map(|p| p.to_array());
+            let bytes: &[u8] = bytemuck::cast_slice(&params);
+            data[i * stride..i * stride + bytes.len()].copy_from_slice(bytes);
+        }
+
+        queue.write_buffer(&self.material_ubo, 0, &data);
+    }
+
+    pub fn material_params_offset(&self, batch_index: usize) -> wgpu::DynamicOffset {
+        (batch_index as u64 * self.material_params_stride) as wgpu::DynamicOffset
+    }
+
+    fn create_material_params_buffer(
+        device: &Device,
+        layout: &BindGroupLayout,
+        stride: u64,
+        count: usize,
+    ) -> (Buffer, BindGroup) {
+        let material_ubo = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("material ubo"),
+            size: stride * count as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let material_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("material bind group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &material_ubo,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(MATERIAL_PARAMS_SIZE),
+                }),
+            }],
+        });
+
+        (material_ubo, material_bind_group)
+    }
+
+    /// Uploads the globals of each camera. The globals of camera `i` live at
+    /// [`Self::globals_offset`]`(i)`.
+    pub fn write_globals(&self, queue: &wgpu::Queue, globals: &[Globals]) {
+        let stride = self.globals_stride as usize;
+        let mut data = vec![0_u8; stride * globals.len()];
+        for (i, globals) in globals.iter().enumerate() {
+            let bytes: &[u8] = bytemuck::bytes_of(globals);
+            data[i * stride..i * stride + bytes.len()].copy_from_slice(bytes);
+        }
+
+        queue.write_buffer(&self.globals_ubo, 0, &data);
+    }
+
+    pub fn globals_offset(&self, camera_index: usize) -> wgpu::DynamicOffset {
+        (camera_index as u64 * self.globals_stride) as wgpu::DynamicOffset
+    }
+
+    fn create_texture_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        sampler: &Sampler,
+        view: &TextureView,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("texture bind group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    fn prepare_texture(&mut self, device: &Device, queue: &wgpu::Queue, texture: &Texture) {
+        if self.textures.contains_key(&texture.id()) {
+            return;
+        }
+
+        let size = wgpu::Extent3d {
+            width: texture.size().x,
+            height: texture.size().y,
+            depth_or_array_layers: 1,
+        };
+
+        // Images are uploaded once, render targets are rendered into like the frame.
+        let view = match texture.pixels() {
+            Some(pixels) => device.create_texture_with_data(
+                queue,
+                &wgpu::TextureDescriptor {
+                    label: Some("image texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                },
+                pixels,
+            ),
+            None => device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.surface_format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_DST,
+            }),
+        }
+        .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = Self::create_texture_bind_group(
+            device,
+            &self.texture_bind_group_layout,
+            &self.texture_sampler,
+            &view,
+        );
+
+        self.textures.insert(
+            texture.id(),
+            GpuTexture {
+                view,
+                bind_group,
+                liveness: texture.liveness(),
+            },
+        );
+    }
+
+    /// Creates the textures sampled by `batches` which do not exist on the GPU yet and releases
+    /// the textures, including those of render targets, whose handles were all dropped.
+    pub fn prepare_textures(
+        &mut self,
+        device: &Device,
+        queue: &wgpu::Queue,
+        batches: &[DrawBatch],
+    ) {
+        self.textures
+            .retain(|_, texture| texture.liveness.strong_count() > 0);
+        self.render_targets
+            .retain(|id, _| self.textures.contains_key(id));
+
+        for texture in batches.iter().filter_map(|batch| batch.texture.as_ref()) {
+            self.prepare_texture(device, queue, texture);
+        }
+    }
+
+    fn texture_bind_group(&self, batch: &DrawBatch) -> &BindGroup {
+        batch
+            .texture
+            .as_ref()
+            .and_then(|texture| self.textures.get(&texture.id()))
+            .map_or(&self.white_texture_bind_group, |texture| {
+                &texture.bind_group
+            })
+    }
+
+    /// Records the draw calls for `batches`, whose material parameters start at batch index
+    /// `first_batch`. The globals and geometry buffers must already be bound.
+    pub fn draw_batches<'a>(
+        &'a self,
+        pass: &mut RenderPass<'a>,
+        batches: &[DrawBatch],
+        first_batch: usize,
+    ) {
+        for (i, batch) in batches.iter().enumerate() {
+            pass.set_pipeline(self.pipeline(batch));
+            pass.set_bind_group(
+                1,
+                &self.material_bind_group,
+                &[self.material_params_offset(first_batch + i)],
+            );
+            pass.set_bind_group(2, self.texture_bind_group(batch), &[]);
+            pass.draw_indexed(batch.indices.clone(), 0, 0..1);
+        }
+    }
+
+    fn prepare_render_target(
+        &mut self,
+        device: &Device,
+        queue: &wgpu::Queue,
+        target: &RenderTarget,
+    ) {
+        let id = target.texture().id();
+        self.prepare_texture(device, queue, target.texture());
+
+        if matches!(self.render_targets.get(&id), Some(r) if r.sample_count == self.msaa_sample_count)
+        {
+            return;
+        }
+
+        let size = wgpu::Extent3d {
+            width: target.size().x,
+            height: target.size().y,
+            depth_or_array_layers: 1,
+        };
+
+        let depth_texture_view = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("render target depth texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: self.msaa_sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: DEPTH_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let multisampled_render_target = (self.msaa_sample_count > 1).then(|| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("render target multisampled texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: self.msaa_sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.surface_format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+
+        let globals_ubo = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("render target globals ubo"),
+            size: std::mem::size_of::<Globals>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let globals_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("render target globals bind group"),
+            layout: &self.globals_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(globals_ubo.as_entire_buffer_binding()),
+            }],
+        });
+
+        self.render_targets.insert(
+            id,
+            RenderTargetResources {
+                sample_count: self.msaa_sample_count,
+                multisampled_render_target,
+                depth_texture_view,
+                globals_ubo,
+                globals_bind_group,
+            },
+        );
+    }
+
+    /// Renders a scene into an offscreen target. Its batches must have been passed to
+    /// [`Self::prepare_pipelines`] and [`Self::write_material_params`] along with the frame's.
+    pub fn render_to_target(
+        &mut self,
+        device: &GraphicsDevice,
+        encoder: &mut CommandEncoder,
+        pass: &TargetPass,
+    ) {
+        self.prepare_render_target(&device.device, &device.queue, pass.target);
+
+        let id = pass.target.texture().id();
+        let resources = &self.render_targets[&id];
+        let view = &self.textures[&id].view;
+
+        device.queue.write_buffer(
+            &resources.globals_ubo,
+            0,
+            bytemuck::cast_slice(&[pass.globals]),
+        );
+
+        let vertex_buffer = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("render target vbo"),
+            contents: bytemuck::cast_slice(pass.vertices),
+            usage: BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("render target ibo"),
+            contents: bytemuck::cast_slice(pass.indices),
+            usage: BufferUsages::INDEX,
+        });
+
+        let clear_color = wgpu::Color {
+            r: pass.clear_color.r as f64,
+            g: pass.clear_color.g as f64,
+            b: pass.clear_color.b as f64,
+            a: pass.clear_color.a as f64,
+        };
+
+        let color_attachment = match &resources.multisampled_render_target {
+            Some(msaa_target) => wgpu::RenderPassColorAttachment {
+                view: msaa_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear_color),
+                    store: true,
+                },
+                resolve_target: Some(view),
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear_color),
+                    store: true,
+                },
+                resolve_target: None,
+            },
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("render target pass"),
+            color_attachments: &[Some(color_attachment)],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &resources.depth_texture_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0.0),
+                    store: false,
+                }),
+                stencil_ops: None,
+            }),
+        });
+
+        // Empty buffers cannot be bound, an empty scene only clears the target.
+        if !pass.batches.is_empty() {
+            render_pass.set_bind_group(0, &resources.globals_bind_group, &[0]);
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            self.draw_batches(&mut render_pass, pass.batches, pass.first_batch);
+        }
+    }
+
+    /// Switches to a new surface format, dropping pipelines built for the old one. Textures share
+    /// the surface format, so they are recreated and lose their contents. Call
+    /// [`Renderer::resize`] afterwards to rebuild the render targets.
+    pub fn set_surface_format(&mut self, surface_format: wgpu::TextureFormat) {
+        if surface_format != self.surface_format {
+            self.surface_format = surface_format;
+            self.pipelines.clear();
+            self.textures.clear();
+            self.render_targets.clear();
+        }
+    }
+
+    /// Pipelines are cached per sample count, so switching back and forth is cheap. Call
+    /// [`Renderer::resize`] afterwards to rebuild the render targets.
+    pub fn set_sample_count(&mut self, msaa_sample_count: u32) {
+        self.msaa_sample_count = msaa_sample_count;
+    }
+
     pub fn resize(&mut self, device: &GraphicsDevice) {
-        let depth_texture = device.device.create_texture(&wgpu::TextureDescriptor {
+        self.depth_texture_view =
+            Self::create_depth_texture_view(&device.device, &device.config, self.msaa_sample_count);
+
+        self.multisampled_render_target = (self.msaa_sample_count > 1).then(|| {
+            Self::create_multisampled_framebuffer(
+                &device.device,
+                &device.config,
+                self.msaa_sample_count,
+            )
+        });
+    }
+
+    fn create_depth_texture_view(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> TextureView {
+        let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("depth texture"),
             size: wgpu::Extent3d {
-                width: device.config.width,
-                height: device.config.height,
+                width: surface_config.width,
+                height: surface_config.height,
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
-            sample_count: self.msaa_sample_count,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth32Float,
+            format: DEPTH_FORMAT,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
         });
 
-        self.depth_texture_view =
-            Some(depth_texture.create_view(&wgpu::TextureViewDescriptor::default()));
-
-        self.multisampled_render_target = if self.msaa_sample_count > 1 {
-            Some(Self::create_multisampled_framebuffer(
-                &device.device,
-                &device.config,
-                self.msaa_sample_count,
-            ))
-        } else {
-            None
-        };
+        depth_texture.create_view(&wgpu::TextureViewDescriptor::default())
     }
 
     pub fn resize_geometry_buffers(
@@ -316,7 +1173,7 @@ impl Renderer {
 
         self.geometry_ibo = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("geometry ibo"),
-            size: (std::mem::size_of::<u16>() * max_geometry_indices) as BufferAddress,
+            size: (std::mem::size_of::<u32>() * max_geometry_indices) as BufferAddress,
             usage: wgpu::BufferUsages::INDEX | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
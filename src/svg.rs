@@ -0,0 +1,402 @@
+//! Loads vector art drawn in editors such as Inkscape into [`Drawable`]s.
+//!
+//! Every visible SVG path, rect, circle, ellipse, line, polyline and polygon becomes one
+//! [`PathShape`], in the order they are painted. Shapes are placed in a shared coordinate space
+//! with the bottom left of the SVG at (0, 0) and y pointing up like the rest of the world, so
+//! drawing them all with the same [`crate::components::Transform`] reproduces the picture.
+//!
+//! Solid fills and strokes, stroke styles, fill rules, opacity and transforms are supported.
+//! Gradients keep all of their stops but always pad beyond their ends. Text, images, patterns,
+//! clip paths, masks and filters are skipped.
+
+use std::sync::Arc;
+
+use glam::Vec2;
+use lyon::path::Path;
+use usvg::{tiny_skia_path::PathSegment, Node, Paint, Transform};
+
+use crate::{
+    components::Drawable,
+    error::{GeometryError, SvgError},
+    graphics::{
+        Color, Fill, FillRule, Geometry, LineCap, LineJoin, OutlineAlignment, PathShape,
+        StrokeStyle, Tessellator,
+    },
+};
+
+/// Reads the SVG file at `path` into tessellated drawables, see the [module docs](self).
+pub fn load(
+    path: impl AsRef<std::path::Path>,
+    tessellator: &mut Tessellator,
+) -> Result<Vec<Drawable>, SvgError> {
+    let data = std::fs::read(path)?;
+    parse(&data, tessellator)
+}
+
+/// Like [`load`] but for SVG data that is already in memory, e.g. from `include_bytes!`.
+pub fn parse(data: &[u8], tessellator: &mut Tessellator) -> Result<Vec<Drawable>, SvgError> {
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default())?;
+
+    // Mirror vertically so the bottom left of the SVG ends up at the origin.
+    let flip = Transform::from_row(1.0, 0.0, 0.0, -1.0, 0.0, tree.size().height());
+
+    let mut drawables = Vec::new();
+    convert_group(tree.root(), flip, 1.0, tessellator, &mut drawables)?;
+
+    Ok(drawables)
+}
+
+fn convert_group(
+    group: &usvg::Group,
+    flip: Transform,
+    opacity: f32,
+    tessellator: &mut Tessellator,
+    drawables: &mut Vec<Drawable>,
+) -> Result<(), GeometryError> {
+    let opacity = opacity * group.opacity().get();
+
+    for node in group.children() {
+        match node {
+            Node::Group(group) => convert_group(group, flip, opacity, tessellator, drawables)?,
+            Node::Path(path) if path.is_visible() => {
+                let transform = flip.pre_concat(path.abs_transform());
+                let mut shape = PathShape::default();
+                shape.path = convert_path(path.data(), transform);
+
+                match path.fill() {
+                    Some(fill) => {
+                        shape.fill_rule = match fill.rule() {
+                            usvg::FillRule::NonZero => FillRule::NonZero,
+                            usvg::FillRule::EvenOdd => FillRule::EvenOdd,
+                        };
+                        shape.fill =
+                            convert_paint(fill.paint(), opacity * fill.opacity().get(), transform);
+                    }
+                    None => shape.fill = Fill::Solid(Color::TRANSPARENT),
+                }
+
+                if let Some(stroke) = path.stroke() {
+                    let alpha = opacity * stroke.opacity().get();
+                    let scale = scale_factor(transform);
+                    shape.outline_thickness = stroke.width().get() * scale;
+                    shape.outline_alignment = OutlineAlignment::Centered;
+                    shape.outline_style = StrokeStyle {
+                        join: match stroke.linejoin() {
+                            usvg::LineJoin::Miter => LineJoin::Miter,
+                            usvg::LineJoin::MiterClip => LineJoin::MiterClip,
+                            usvg::LineJoin::Round => LineJoin::Round,
+                            usvg::LineJoin::Bevel => LineJoin::Bevel,
+                        },
+                        cap: match stroke.linecap() {
+                            usvg::LineCap::Butt => LineCap::Butt,
+                            usvg::LineCap::Round => LineCap::Round,
+                            usvg::LineCap::Square => LineCap::Square,
+                        },
+                        miter_limit: stroke.miterlimit().get(),
+                        dashes: stroke
+                            .dasharray()
+                            .map(|dashes| dashes.iter().map(|length| length * scale).collect())
+                            .unwrap_or_default(),
+                        dash_offset: stroke.dashoffset() * scale,
+                    };
+                    shape.outline_color = match stroke.paint() {
+                        Paint::Color(color) => convert_color(*color, alpha),
+                        // Outlines are a single color, so gradients use their first stop.
+                        Paint::LinearGradient(gradient) => stop_color(gradient.stops(), 0, alpha),
+                        Paint::RadialGradient(gradient) => stop_color(gradient.stops(), 0, alpha),
+                        Paint::Pattern(_) => {
+                            log::warn!("SVG pattern strokes are not supported");
+                            Color::TRANSPARENT
+                        }
+                    };
+                }
+
+                shape.update(tessellator)?;
+                drawables.push(Drawable::Path(shape));
+            }
+            Node::Path(_) => {}
+            Node::Image(_) => log::warn!("SVG images are not supported"),
+            Node::Text(_) => log::warn!("SVG text is not supported"),
+        }
+    }
+
+    Ok(())
+}
+
+fn convert_path(data: &usvg::tiny_skia_path::Path, transform: Transform) -> Path {
+    let map = |p: usvg::tiny_skia_path::Point| {
+        let mut p = [p];
+        transform.map_points(&mut p);
+        lyon::geom::point(p[0].x, p[0].y)
+    };
+
+    let mut builder = Path::svg_builder();
+    for segment in data.segments() {
+        match segment {
+            PathSegment::MoveTo(to) => {
+                builder.move_to(map(to));
+            }
+            PathSegment::LineTo(to) => {
+                builder.line_to(map(to));
+            }
+            PathSegment::QuadTo(control, to) => {
+                builder.quadratic_bezier_to(map(control), map(to));
+            }
+            PathSegment::CubicTo(control1, control2, to) => {
+                builder.cubic_bezier_to(map(control1), map(control2), map(to));
+            }
+            PathSegment::Close => builder.close(),
+        }
+    }
+
+    builder.build()
+}
+
+/// How much `transform` scales lengths on average, for stroke widths and radii.
+fn scale_factor(transform: Transform) -> f32 {
+    (transform.sx * transform.sy - transform.kx * transform.ky)
+        .abs()
+        .sqrt()
+}
+
+fn map_point(transform: Transform, x: f32, y: f32) -> Vec2 {
+    let mut p = [usvg::tiny_skia_path::Point::from_xy(x, y)];
+    transform.map_points(&mut p);
+    Vec2::new(p[0].x, p[0].y)
+}
+
+fn convert_paint(paint: &Paint, opacity: f32, transform: Transform) -> Fill {
+    match paint {
+        Paint::Color(color) => Fill::Solid(convert_color(*color, opacity)),
+        Paint::LinearGradient(gradient) => {
+            let transform = transform.pre_concat(gradient.transform());
+            let start = map_point(transform, gradient.x1(), gradient.y1());
+            let end = map_point(transform, gradient.x2(), gradient.y2());
+            let stops = convert_stops(gradient.stops(), opacity);
+
+            match stops.as_slice() {
+                [(_, start_color), (_, end_color)] if is_padded(&stops) => Fill::LinearGradient {
+                    start,
+                    end,
+                    start_color: *start_color,
+                    end_color: *end_color,
+                },
+                _ => {
+                    let axis = end - start;
+                    let length_squared = axis.length_squared().max(f32::EPSILON);
+                    Fill::Custom(Arc::new(move |position| {
+                        let t = (position - start).dot(axis) / length_squared;
+                        gradient_color(&stops, t)
+                    }))
+                }
+            }
+        }
+        Paint::RadialGradient(gradient) => {
+            // The focal point is ignored; the gradient is centered on (cx, cy).
+            let transform = transform.pre_concat(gradient.transform());
+            let center = map_point(transform, gradient.cx(), gradient.cy());
+            let radius = gradient.r().get() * scale_factor(transform);
+            let stops = convert_stops(gradient.stops(), opacity);
+
+            match stops.as_slice() {
+                [(_, inner_color), (_, outer_color)] if is_padded(&stops) => Fill::RadialGradient {
+                    center,
+                    radius,
+                    inner_color: *inner_color,
+                    outer_color: *outer_color,
+                },
+                _ => Fill::Custom(Arc::new(move |position| {
+                    let t = position.distance(center) / radius.max(f32::EPSILON);
+                    gradient_color(&stops, t)
+                })),
+            }
+        }
+        Paint::Pattern(_) => {
+            log::warn!("SVG pattern fills are not supported");
+            Fill::Solid(Color::TRANSPARENT)
+        }
+    }
+}
+
+fn convert_stops(stops: &[usvg::Stop], opacity: f32) -> Vec<(f32, Color)> {
+    (0..stops.len())
+        .map(|i| (stops[i].offset().get(), stop_color(stops, i, opacity)))
+        .collect()
+}
+
+fn stop_color(stops: &[usvg::Stop], i: usize, opacity: f32) -> Color {
+    match stops.get(i) {
+        Some(stop) => convert_color(stop.color(), opacity * stop.opacity().get()),
+        None => Color::TRANSPARENT,
+    }
+}
+
+/// Whether two stops span the whole gradient, so it matches one of [`Fill`]'s gradients.
+fn is_padded(stops: &[(f32, Color)]) -> bool {
+    stops.first().map(|(offset, _)| *offset) == Some(0.0)
+        && stops.last().map(|(offset, _)| *offset) == Some(1.0)
+}
+
+fn gradient_color(stops: &[(f32, Color)], t: f32) -> Color {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Color::TRANSPARENT,
+    };
+
+    if t <= first.0 {
+        return first.1;
+    }
+
+    for pair in stops.windows(2) {
+        let ((from, from_color), (to, to_color)) = (pair[0], pair[1]);
+        if t <= to {
+            let range = (to - from).max(f32::EPSILON);
+            return from_color.lerp(to_color, (t - from) / range);
+        }
+    }
+
+    last.1
+}
+
+/// SVG colors are sRGB encoded, while shapes are colored in linear space.
+fn convert_color(color: usvg::Color, alpha: f32) -> Color {
+    let [r, g, b] = [color.red, color.green, color.blue].map(|c| c as f32 / 255.0);
+    Color::from_srgb(r, g, b, alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Rect;
+
+    fn parse_one(svg: &str) -> PathShape {
+        let mut drawables = parse(svg.as_bytes(), &mut Tessellator::new(0.01)).unwrap();
+        assert_eq!(drawables.len(), 1);
+        match drawables.pop() {
+            Some(Drawable::Path(shape)) => shape,
+            _ => unreachable!("SVG paths become path shapes"),
+        }
+    }
+
+    fn bounds(shape: &PathShape) -> Rect {
+        Drawable::Path(shape.clone()).local_bounds().unwrap()
+    }
+
+    #[test]
+    fn origin_is_bottom_left() {
+        let shape = parse_one(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50">
+                <rect x="10" y="0" width="20" height="10"/>
+            </svg>"#,
+        );
+
+        let bounds = bounds(&shape);
+        assert!(bounds.position.abs_diff_eq(Vec2::new(10.0, 40.0), 1e-3));
+        assert!(bounds.size.abs_diff_eq(Vec2::new(20.0, 10.0), 1e-3));
+    }
+
+    #[test]
+    fn nested_groups_combine_transforms_and_opacity() {
+        let shape = parse_one(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+                <g transform="translate(10 0)" opacity="0.5">
+                    <g transform="scale(2)" opacity="0.5">
+                        <rect width="10" height="10" fill="white"/>
+                    </g>
+                </g>
+            </svg>"#,
+        );
+
+        let bounds = bounds(&shape);
+        assert!(bounds.position.abs_diff_eq(Vec2::new(10.0, 80.0), 1e-3));
+        assert!(bounds.size.abs_diff_eq(Vec2::new(20.0, 20.0), 1e-3));
+        match shape.fill {
+            Fill::Solid(color) => assert!((color.a - 0.25).abs() < 1e-6, "{:?}", color),
+            _ => panic!("expected a solid fill"),
+        }
+    }
+
+    #[test]
+    fn fill_rules_decide_whether_overlaps_are_filled() {
+        let svg = |rule: &str| {
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+                    <path fill-rule="{}" d="M0 0 H100 V100 H0 Z M25 25 H75 V75 H25 Z"/>
+                </svg>"#,
+                rule
+            )
+        };
+        let center = Vec2::new(50.0, 50.0);
+        let corner = Vec2::new(10.0, 10.0);
+
+        let even_odd = Drawable::Path(parse_one(&svg("evenodd")));
+        assert!(!even_odd.contains_point(center, &Default::default()));
+        assert!(even_odd.contains_point(corner, &Default::default()));
+
+        let non_zero = Drawable::Path(parse_one(&svg("nonzero")));
+        assert!(non_zero.contains_point(center, &Default::default()));
+        assert!(non_zero.contains_point(corner, &Default::default()));
+    }
+
+    #[test]
+    fn stroke_width_is_scaled_by_the_transform() {
+        let shape = parse_one(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+                <g transform="scale(3)">
+                    <path d="M0 10 H20" fill="none" stroke="black" stroke-width="2"/>
+                </g>
+            </svg>"#,
+        );
+
+        assert!((shape.outline_thickness - 6.0).abs() < 1e-4);
+        assert!((bounds(&shape).size.y - 6.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gradients_with_more_than_two_stops_are_custom_fills() {
+        let svg = |stops: &str| {
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+                    <linearGradient id="g" gradientUnits="userSpaceOnUse"
+                        x1="0" y1="0" x2="100" y2="0">
+                        {}
+                    </linearGradient>
+                    <rect width="100" height="100" fill="url(#g)"/>
+                </svg>"#,
+                stops
+            )
+        };
+
+        let two_stops = parse_one(&svg(r#"<stop offset="0" stop-color="black"/>
+            <stop offset="1" stop-color="white"/>"#));
+        match two_stops.fill {
+            Fill::LinearGradient { start, end, .. } => {
+                assert!(start.abs_diff_eq(Vec2::new(0.0, 100.0), 1e-3));
+                assert!(end.abs_diff_eq(Vec2::new(100.0, 100.0), 1e-3));
+            }
+            _ => panic!("expected a linear gradient"),
+        }
+
+        let three_stops = parse_one(&svg(r#"<stop offset="0" stop-color="black"/>
+            <stop offset="0.5" stop-color="red"/>
+            <stop offset="1" stop-color="white"/>"#));
+        match three_stops.fill {
+            Fill::Custom(color_at) => {
+                let middle = color_at(Vec2::new(50.0, 50.0));
+                let [r, g, b, a] = middle.to_array();
+                assert!(
+                    r > 0.999 && g < 1e-3 && b < 1e-3 && a > 0.999,
+                    "{:?}",
+                    middle
+                );
+            }
+            _ => panic!("expected a custom fill"),
+        }
+    }
+
+    #[test]
+    fn unreadable_data_is_a_parse_error() {
+        let result = parse(b"not an svg", &mut Tessellator::new(0.01));
+        assert!(matches!(result, Err(SvgError::Parse(_))));
+    }
+}
@@ -0,0 +1,212 @@
+//! Textures that sprites are drawn with.
+//!
+//! Textures are handles; the renderer creates the GPU texture the first time one is drawn or
+//! rendered to. Images are loaded with [`Texture::from_png`]. A [`RenderTarget`] is a texture
+//! that a [`crate::Scene`] can be rendered into with [`crate::Scene::render_to`], after which it
+//! can be drawn with a [`crate::graphics::SpriteShape`] like any other texture. Its contents
+//! persist until it is rendered to again, so a static background only needs to be rendered once.
+//!
+//! Materials can sample the texture of the shape they draw:
+//!
+//! ```wgsl
+//! @group(2) @binding(0) var texture: texture_2d<f32>;
+//! @group(2) @binding(1) var texture_sampler: sampler;
+//! ```
+//!
+//! Shapes without a texture are drawn with a 1×1 white texture. The GPU texture is released
+//! once the last clone of its handle has been dropped.
+
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Weak,
+    },
+};
+
+use glam::UVec2;
+
+use crate::{error::TextureError, graphics::Color};
+
+static NEXT_TEXTURE_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct TextureId(u64);
+
+/// A cheap to clone reference to a texture.
+#[derive(Debug, Clone)]
+pub struct Texture {
+    id: TextureId,
+    size: UVec2,
+    /// Shared by all clones so the renderer can tell when the last one was dropped.
+    alive: Arc<()>,
+    /// sRGB encoded RGBA texels with premultiplied alpha, row by row, for images. Render targets
+    /// have none.
+    pixels: Option<Arc<[u8]>>,
+}
+
+impl Texture {
+    fn new(width: u32, height: u32) -> Self {
+        let id = TextureId(NEXT_TEXTURE_ID.fetch_add(1, Ordering::Relaxed));
+
+        Self {
+            id,
+            size: UVec2::new(width.max(1), height.max(1)),
+            alive: Arc::new(()),
+            pixels: None,
+        }
+    }
+
+    /// Loads the PNG image at `path`, see [`Texture::from_png_bytes`].
+    pub fn from_png(path: impl AsRef<Path>) -> Result<Self, TextureError> {
+        let data = std::fs::read(path)?;
+        Self::from_png_bytes(&data)
+    }
+
+    /// Decodes a PNG image that is already in memory, e.g. one embedded with `include_bytes!`.
+    /// Images of any color type and bit depth are converted to 8-bit RGBA.
+    pub fn from_png_bytes(data: &[u8]) -> Result<Self, TextureError> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let pixels = rgba8(&buffer[..info.buffer_size()], info.color_type);
+
+        let mut texture = Self::new(info.width, info.height);
+        texture.pixels = Some(premultiply(pixels).into());
+        Ok(texture)
+    }
+
+    /// Size of the texture in texels.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    pub(crate) fn id(&self) -> TextureId {
+        self.id
+    }
+
+    /// The texels to upload, see [`Texture::pixels`]. Render targets have none.
+    pub(crate) fn pixels(&self) -> Option<&[u8]> {
+        self.pixels.as_deref()
+    }
+
+    /// Whether any clone of the texture still exists, for the renderer to release textures
+    /// that can no longer be drawn.
+    pub(crate) fn liveness(&self) -> Weak<()> {
+        Arc::downgrade(&self.alive)
+    }
+}
+
+/// Expands 8-bit texels of any PNG color type to RGBA.
+fn rgba8(texels: &[u8], color_type: png::ColorType) -> Vec<u8> {
+    match color_type {
+        png::ColorType::Rgba => texels.to_vec(),
+        png::ColorType::Rgb => texels
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => texels
+            .chunks_exact(2)
+            .flat_map(|gray| [gray[0], gray[0], gray[0], gray[1]])
+            .collect(),
+        // Indexed images are expanded to RGB or RGBA by the decoder.
+        png::ColorType::Grayscale | png::ColorType::Indexed => texels
+            .iter()
+            .flat_map(|&gray| [gray, gray, gray, 255])
+            .collect(),
+    }
+}
+
+/// Multiplies the color of RGBA texels by their alpha in linear space, which is the form the
+/// renderer blends in.
+fn premultiply(mut pixels: Vec<u8>) -> Vec<u8> {
+    for texel in pixels.chunks_exact_mut(4) {
+        if texel[3] == 255 {
+            continue;
+        }
+
+        let [r, g, b, a] = [texel[0], texel[1], texel[2], texel[3]].map(|c| c as f32 / 255.0);
+        let color = Color::from_srgb(r, g, b, a).premultiplied().to_srgb();
+        for (channel, value) in texel.iter_mut().zip([color.r, color.g, color.b]) {
+            *channel = (value * 255.0).round() as u8;
+        }
+    }
+    pixels
+}
+
+/// An offscreen texture to render a scene into, e.g. for minimaps, picture-in-picture or cached
+/// backgrounds. Clones refer to the same texture.
+#[derive(Debug, Clone)]
+pub struct RenderTarget {
+    texture: Texture,
+}
+
+impl RenderTarget {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            texture: Texture::new(width, height),
+        }
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.texture.size
+    }
+
+    /// The texture the target renders into, for drawing with a sprite.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_png(width: u32, height: u32, color_type: png::ColorType, texels: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(texels).unwrap();
+        drop(writer);
+        data
+    }
+
+    #[test]
+    fn pngs_load_as_premultiplied_rgba() {
+        let rgba = encode_png(
+            2,
+            1,
+            png::ColorType::Rgba,
+            &[255, 0, 0, 255, 255, 255, 255, 0],
+        );
+        let texture = Texture::from_png_bytes(&rgba).unwrap();
+        assert_eq!(texture.size(), UVec2::new(2, 1));
+        assert_eq!(texture.pixels(), Some(&[255, 0, 0, 255, 0, 0, 0, 0][..]));
+
+        let gray = encode_png(1, 1, png::ColorType::Grayscale, &[128]);
+        let texture = Texture::from_png_bytes(&gray).unwrap();
+        assert_eq!(texture.pixels(), Some(&[128, 128, 128, 255][..]));
+
+        assert!(matches!(
+            Texture::from_png_bytes(b"not a png"),
+            Err(TextureError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn textures_die_with_their_last_clone() {
+        let target = RenderTarget::new(16, 16);
+        let liveness = target.texture().liveness();
+
+        let texture = target.texture().clone();
+        drop(target);
+        assert_eq!(liveness.strong_count(), 1);
+
+        drop(texture);
+        assert_eq!(liveness.strong_count(), 0);
+    }
+}
@@ -0,0 +1,693 @@
+//! Animates transforms and shapes over time.
+//!
+//! Add a [`Tweens`] component to an entity and call [`update_tweens`] from
+//! [`crate::Game::on_update`], so tweens advance with the fixed update:
+//!
+//! ```ignore
+//! let fade = Tween::new(
+//!     TweenProperty::Alpha { from: 1.0, to: 0.0 },
+//!     Duration::from_secs(1),
+//!     Easing::QuadOut,
+//! );
+//! world.spawn((transform, drawable, Tweens::from(fade)));
+//!
+//! for completed in update_tweens(&mut world, &mut tessellator, dt) {
+//!     // ...
+//! }
+//! ```
+//!
+//! Tweens set the properties they animate on every update, so they override changes made by
+//! other systems while they run. Shapes whose parameters change are updated with the tessellator
+//! passed to [`update_tweens`].
+
+use std::{f32::consts::PI, fmt, time::Duration};
+
+use glam::Vec2;
+use hecs::{Entity, World};
+
+use crate::{
+    components::{Drawable, Transform},
+    graphics::{Color, Fill, Geometry, Tessellator},
+};
+
+/// How a tween's progress is mapped to the progress of the animated value. `In` curves start
+/// slowly, `Out` curves end slowly and `InOut` curves do both.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    /// Pulls back before moving towards the target.
+    BackIn,
+    /// Overshoots the target before settling.
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    /// Springs past the target and oscillates around it.
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    /// Bounces against the target like a dropped ball.
+    BounceOut,
+    BounceInOut,
+}
+
+impl Easing {
+    /// Maps `t` in `0..=1` to the value's progress, which is 0 at `t = 0` and 1 at `t = 1` but may
+    /// leave that range in between, e.g. for [`Easing::BackOut`].
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => power_in(t, 2),
+            Easing::QuadOut => power_out(t, 2),
+            Easing::QuadInOut => power_in_out(t, 2),
+            Easing::CubicIn => power_in(t, 3),
+            Easing::CubicOut => power_out(t, 3),
+            Easing::CubicInOut => power_in_out(t, 3),
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => (1.0 - (t * PI).cos()) / 2.0,
+            Easing::ExpoIn => {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    2.0_f32.powf(10.0 * t - 10.0)
+                }
+            }
+            Easing::ExpoOut => 1.0 - Easing::ExpoIn.apply(1.0 - t),
+            Easing::ExpoInOut => in_out(t, |t| Easing::ExpoIn.apply(t)),
+            Easing::BackIn => {
+                const OVERSHOOT: f32 = 1.70158;
+                (OVERSHOOT + 1.0) * t * t * t - OVERSHOOT * t * t
+            }
+            Easing::BackOut => 1.0 - Easing::BackIn.apply(1.0 - t),
+            Easing::BackInOut => in_out(t, |t| Easing::BackIn.apply(t)),
+            Easing::ElasticIn => {
+                if t <= 0.0 || t >= 1.0 {
+                    t
+                } else {
+                    -(2.0_f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * 2.0 * PI / 3.0).sin()
+                }
+            }
+            Easing::ElasticOut => 1.0 - Easing::ElasticIn.apply(1.0 - t),
+            Easing::ElasticInOut => in_out(t, |t| Easing::ElasticIn.apply(t)),
+            Easing::BounceIn => 1.0 - Easing::BounceOut.apply(1.0 - t),
+            Easing::BounceOut => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+            Easing::BounceInOut => in_out(t, |t| Easing::BounceIn.apply(t)),
+        }
+    }
+}
+
+fn power_in(t: f32, power: i32) -> f32 {
+    t.powi(power)
+}
+
+fn power_out(t: f32, power: i32) -> f32 {
+    1.0 - (1.0 - t).powi(power)
+}
+
+fn power_in_out(t: f32, power: i32) -> f32 {
+    in_out(t, |t| power_in(t, power))
+}
+
+/// Plays the `ease_in` curve over the first half and its mirror image over the second.
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        ease_in(2.0 * t) / 2.0
+    } else {
+        1.0 - ease_in(2.0 - 2.0 * t) / 2.0
+    }
+}
+
+/// A value animated by a tween, from `from` at its start to `to` at its end.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TweenProperty {
+    /// [`Transform::translation`].
+    Translation { from: Vec2, to: Vec2 },
+    /// [`Transform::rotation`] in degrees.
+    Rotation { from: f32, to: f32 },
+    /// [`Transform::scale`].
+    Scale { from: Vec2, to: Vec2 },
+    /// The solid fill of shapes with a fill, and the color of sprites.
+    FillColor { from: Color, to: Color },
+    /// The outline color of every shape but sprites.
+    OutlineColor { from: Color, to: Color },
+    /// The alpha of every color of the shape. Custom fills keep their own alpha.
+    Alpha { from: f32, to: f32 },
+    /// The size of rectangles and sprites, the radii of ellipses and the radius and inner radius
+    /// of rings. Circles, polygons, arcs and sectors use `x` as their radius and lines as their
+    /// length. Paths and polylines have no size.
+    Size { from: Vec2, to: Vec2 },
+}
+
+impl TweenProperty {
+    /// Sets the value at `progress` between `from` and `to`. Returns whether the shape's
+    /// parameters changed and it has to be updated.
+    fn apply(
+        &self,
+        progress: f32,
+        transform: Option<&mut Transform>,
+        drawable: Option<&mut Drawable>,
+    ) -> bool {
+        match *self {
+            TweenProperty::Translation { from, to } => {
+                if let Some(transform) = transform {
+                    transform.translation = from.lerp(to, progress);
+                }
+                false
+            }
+            TweenProperty::Rotation { from, to } => {
+                if let Some(transform) = transform {
+                    transform.rotation = from + (to - from) * progress;
+                }
+                false
+            }
+            TweenProperty::Scale { from, to } => {
+                if let Some(transform) = transform {
+                    transform.scale = from.lerp(to, progress);
+                }
+                false
+            }
+            TweenProperty::FillColor { from, to } => {
+                drawable.is_some_and(|drawable| set_fill_color(drawable, from.lerp(to, progress)))
+            }
+            TweenProperty::OutlineColor { from, to } => drawable
+                .is_some_and(|drawable| set_outline_color(drawable, from.lerp(to, progress))),
+            TweenProperty::Alpha { from, to } => {
+                drawable.is_some_and(|drawable| set_alpha(drawable, from + (to - from) * progress))
+            }
+            TweenProperty::Size { from, to } => {
+                drawable.is_some_and(|drawable| set_size(drawable, from.lerp(to, progress)))
+            }
+        }
+    }
+}
+
+fn fill_mut(drawable: &mut Drawable) -> Option<&mut Fill> {
+    match drawable {
+        Drawable::Circle(circle) => Some(&mut circle.fill),
+        Drawable::Polygon(polygon) => Some(&mut polygon.fill),
+        Drawable::Rect(rect) => Some(&mut rect.fill),
+        Drawable::Path(path) => Some(&mut path.fill),
+        Drawable::RoundedRect(rect) => Some(&mut rect.fill),
+        Drawable::Ellipse(ellipse) => Some(&mut ellipse.fill),
+        Drawable::Sector(sector) => Some(&mut sector.fill),
+        Drawable::Ring(ring) => Some(&mut ring.fill),
+        Drawable::Line(_) | Drawable::Sprite(_) | Drawable::Arc(_) | Drawable::Polyline(_) => None,
+    }
+}
+
+fn outline_color_mut(drawable: &mut Drawable) -> Option<&mut Color> {
+    match drawable {
+        Drawable::Circle(circle) => Some(&mut circle.outline_color),
+        Drawable::Line(line) => Some(&mut line.outline_color),
+        Drawable::Polygon(polygon) => Some(&mut polygon.outline_color),
+        Drawable::Rect(rect) => Some(&mut rect.outline_color),
+        Drawable::Sprite(_) => None,
+        Drawable::Path(path) => Some(&mut path.outline_color),
+        Drawable::RoundedRect(rect) => Some(&mut rect.outline_color),
+        Drawable::Ellipse(ellipse) => Some(&mut ellipse.outline_color),
+        Drawable::Arc(arc) => Some(&mut arc.outline_color),
+        Drawable::Sector(sector) => Some(&mut sector.outline_color),
+        Drawable::Ring(ring) => Some(&mut ring.outline_color),
+        Drawable::Polyline(polyline) => Some(&mut polyline.outline_color),
+    }
+}
+
+fn set_fill_color(drawable: &mut Drawable, color: Color) -> bool {
+    if let Drawable::Sprite(sprite) = drawable {
+        return replace(&mut sprite.color, color);
+    }
+
+    match fill_mut(drawable) {
+        Some(Fill::Solid(current)) if *current == color => false,
+        Some(fill) => {
+            *fill = Fill::Solid(color);
+            true
+        }
+        None => false,
+    }
+}
+
+fn set_outline_color(drawable: &mut Drawable, color: Color) -> bool {
+    outline_color_mut(drawable).is_some_and(|current| replace(current, color))
+}
+
+fn set_alpha(drawable: &mut Drawable, alpha: f32) -> bool {
+    let mut changed = match drawable {
+        Drawable::Sprite(sprite) => return replace(&mut sprite.color.a, alpha),
+        Drawable::Polyline(polyline) => polyline.colors.iter_mut().fold(false, |changed, color| {
+            replace(&mut color.a, alpha) | changed
+        }),
+        _ => false,
+    };
+    if let Some(fill) = fill_mut(drawable) {
+        changed |= fill.set_alpha(alpha);
+    }
+    if let Some(color) = outline_color_mut(drawable) {
+        changed |= replace(&mut color.a, alpha);
+    }
+    changed
+}
+
+fn set_size(drawable: &mut Drawable, size: Vec2) -> bool {
+    match drawable {
+        Drawable::Circle(circle) => replace(&mut circle.radius, size.x),
+        Drawable::Line(line) => replace(&mut line.length, size.x),
+        Drawable::Polygon(polygon) => replace(&mut polygon.radius, size.x),
+        Drawable::Rect(rect) => replace(&mut rect.size, size),
+        Drawable::Sprite(sprite) => replace(&mut sprite.size, size),
+        Drawable::RoundedRect(rect) => replace(&mut rect.size, size),
+        Drawable::Ellipse(ellipse) => replace(&mut ellipse.radii, size),
+        Drawable::Arc(arc) => replace(&mut arc.radius, size.x),
+        Drawable::Sector(sector) => replace(&mut sector.radius, size.x),
+        Drawable::Ring(ring) => {
+            replace(&mut ring.radius, size.x) | replace(&mut ring.inner_radius, size.y)
+        }
+        Drawable::Path(_) | Drawable::Polyline(_) => false,
+    }
+}
+
+/// Sets `current` to `value`, returning whether it changed.
+fn replace<T: PartialEq>(current: &mut T, value: T) -> bool {
+    let changed = *current != value;
+    *current = value;
+    changed
+}
+
+/// One part of a tween's sequence.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TweenStep {
+    pub property: TweenProperty,
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+/// How many times a tween plays its sequence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Repeat {
+    Times(u32),
+    /// The tween never completes.
+    Forever,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat::Times(1)
+    }
+}
+
+/// Called with the world and the tween's entity when a tween completes.
+pub type TweenCallback = Box<dyn FnMut(&mut World, Entity) + Send + Sync>;
+
+/// Plays a sequence of steps, each animating one property. Properties animated by several steps
+/// hold the end value of the last step that finished, or the start value of the first one that
+/// has yet to start.
+pub struct Tween {
+    pub steps: Vec<TweenStep>,
+    pub repeat: Repeat,
+    /// Plays every other repetition backwards.
+    pub yoyo: bool,
+    /// Identifies the tween in [`TweenCompleted`] events.
+    pub name: String,
+    /// Called with the tween's entity when the tween completes, after its [`Tweens`] component
+    /// was removed if no other tweens remain.
+    pub on_complete: Option<TweenCallback>,
+    elapsed: Duration,
+}
+
+impl Tween {
+    /// A tween playing a single step once.
+    pub fn new(property: TweenProperty, duration: Duration, easing: Easing) -> Self {
+        Self {
+            steps: vec![TweenStep {
+                property,
+                duration,
+                easing,
+            }],
+            repeat: Repeat::default(),
+            yoyo: false,
+            name: String::new(),
+            on_complete: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Appends a step that starts when the previous one ends.
+    pub fn then(mut self, property: TweenProperty, duration: Duration, easing: Easing) -> Self {
+        self.steps.push(TweenStep {
+            property,
+            duration,
+            easing,
+        });
+        self
+    }
+
+    /// Time since the tween started, including previous repetitions.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Length of one play through the sequence.
+    pub fn sequence_duration(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        match self.repeat {
+            Repeat::Times(times) => self.elapsed >= self.sequence_duration() * times,
+            Repeat::Forever => false,
+        }
+    }
+
+    /// Position within the sequence in seconds, counted from its start even when playing
+    /// backwards.
+    fn sequence_time(&self) -> f32 {
+        let duration = self.sequence_duration().as_secs_f32();
+        if duration <= 0.0 {
+            return duration;
+        }
+
+        let (play, time) = match self.repeat {
+            Repeat::Times(times) if self.is_complete() => (times.saturating_sub(1), duration),
+            _ => {
+                let elapsed = self.elapsed.as_secs_f32();
+                let play = (elapsed / duration).floor();
+                (play as u32, elapsed - play * duration)
+            }
+        };
+
+        if self.yoyo && play % 2 == 1 {
+            duration - time
+        } else {
+            time
+        }
+    }
+
+    /// Sets every animated value for the current time. Returns whether the shape has to be
+    /// updated.
+    fn apply(
+        &self,
+        mut transform: Option<&mut Transform>,
+        mut drawable: Option<&mut Drawable>,
+    ) -> bool {
+        let time = self.sequence_time();
+        let mut start = 0.0;
+        let progress = self
+            .steps
+            .iter()
+            .map(|step| {
+                let duration = step.duration.as_secs_f32();
+                let progress = if duration > 0.0 {
+                    (time - start) / duration
+                } else if time >= start {
+                    1.0
+                } else {
+                    0.0
+                };
+                start += duration;
+                progress.clamp(0.0, 1.0)
+            })
+            .collect::<Vec<_>>();
+        let Some(current) = progress.iter().rposition(|&p| p > 0.0).or(
+            // Before the first step has made progress, it is still the current one.
+            (!self.steps.is_empty()).then_some(0),
+        ) else {
+            return false;
+        };
+
+        // Later steps are applied first so that the start values of the earliest ones win, and
+        // earlier steps in order so that the latest end values win.
+        let order = (current + 1..self.steps.len())
+            .rev()
+            .chain(0..current)
+            .chain([current]);
+        let mut changed = false;
+        for i in order {
+            let step = &self.steps[i];
+            let progress = step.easing.apply(progress[i]);
+            changed |=
+                step.property
+                    .apply(progress, transform.as_deref_mut(), drawable.as_deref_mut());
+        }
+        changed
+    }
+}
+
+impl fmt::Debug for Tween {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tween")
+            .field("steps", &self.steps)
+            .field("repeat", &self.repeat)
+            .field("yoyo", &self.yoyo)
+            .field("name", &self.name)
+            .field("elapsed", &self.elapsed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The tweens playing on an entity. They run side by side, each animating its own properties.
+#[derive(Debug, Default)]
+pub struct Tweens(pub Vec<Tween>);
+
+impl From<Tween> for Tweens {
+    fn from(tween: Tween) -> Self {
+        Self(vec![tween])
+    }
+}
+
+/// Reported by [`update_tweens`] for every tween that completed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TweenCompleted {
+    pub entity: Entity,
+    pub name: String,
+}
+
+/// Advances every entity's [`Tweens`] by `dt` and applies them to its [`Transform`] and
+/// [`Drawable`], updating shapes whose parameters changed with `tessellator`. Completed tweens
+/// are removed, their callbacks called and their completion returned.
+pub fn update_tweens(
+    world: &mut World,
+    tessellator: &mut Tessellator,
+    dt: Duration,
+) -> Vec<TweenCompleted> {
+    let mut completed = Vec::new();
+    for (entity, (tweens, mut transform, mut drawable)) in
+        world.query_mut::<(&mut Tweens, Option<&mut Transform>, Option<&mut Drawable>)>()
+    {
+        let mut changed = false;
+        for tween in &mut tweens.0 {
+            tween.elapsed += dt;
+            changed |= tween.apply(transform.as_deref_mut(), drawable.as_deref_mut());
+        }
+
+        if changed {
+            if let Some(drawable) = drawable {
+                if let Err(e) = drawable.update(tessellator) {
+                    log::warn!("{}", e);
+                }
+            }
+        }
+
+        let (finished, running) = std::mem::take(&mut tweens.0)
+            .into_iter()
+            .partition::<Vec<_>, _>(Tween::is_complete);
+        tweens.0 = running;
+        completed.extend(finished.into_iter().map(|tween| (entity, tween)));
+    }
+
+    completed
+        .into_iter()
+        .map(|(entity, mut tween)| {
+            if world
+                .get::<&Tweens>(entity)
+                .is_ok_and(|tweens| tweens.0.is_empty())
+            {
+                let _ = world.remove_one::<Tweens>(entity);
+            }
+            if let Some(on_complete) = &mut tween.on_complete {
+                on_complete(world, entity);
+            }
+            TweenCompleted {
+                entity,
+                name: tween.name,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::graphics::{CircleShape, PolylineShape};
+
+    const EASINGS: [Easing; 22] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::ExpoIn,
+        Easing::ExpoOut,
+        Easing::ExpoInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::ElasticInOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::BounceInOut,
+    ];
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for easing in EASINGS {
+            assert!(easing.apply(0.0).abs() < 1e-3, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-3, "{:?}", easing);
+            if format!("{:?}", easing).ends_with("InOut") {
+                assert!((easing.apply(0.5) - 0.5).abs() < 1e-3, "{:?}", easing);
+            }
+        }
+        assert!(Easing::QuadIn.apply(0.5) < 0.5);
+        assert!(Easing::QuadOut.apply(0.5) > 0.5);
+    }
+
+    #[test]
+    fn sequences_repeat_and_yoyo() {
+        let second = Duration::from_secs(1);
+        let mut tween = Tween::new(
+            TweenProperty::Translation {
+                from: Vec2::ZERO,
+                to: Vec2::new(10.0, 0.0),
+            },
+            second,
+            Easing::Linear,
+        )
+        .then(
+            TweenProperty::Translation {
+                from: Vec2::new(10.0, 0.0),
+                to: Vec2::new(10.0, 10.0),
+            },
+            second,
+            Easing::Linear,
+        );
+        tween.repeat = Repeat::Times(2);
+        tween.yoyo = true;
+
+        let mut transform = Transform::default();
+        let mut at = |tween: &mut Tween, seconds: f32| {
+            tween.elapsed = Duration::from_secs_f32(seconds);
+            tween.apply(Some(&mut transform), None);
+            transform.translation
+        };
+
+        assert_eq!(at(&mut tween, 0.0), Vec2::ZERO);
+        assert_eq!(at(&mut tween, 0.5), Vec2::new(5.0, 0.0));
+        assert_eq!(at(&mut tween, 1.5), Vec2::new(10.0, 5.0));
+        // The second play runs backwards.
+        assert_eq!(at(&mut tween, 2.5), Vec2::new(10.0, 5.0));
+        assert_eq!(at(&mut tween, 3.5), Vec2::new(5.0, 0.0));
+        assert!(!tween.is_complete());
+        assert_eq!(at(&mut tween, 5.0), Vec2::ZERO);
+        assert!(tween.is_complete());
+    }
+
+    #[test]
+    fn alpha_changes_only_when_a_color_changes() {
+        let fade = TweenProperty::Alpha { from: 1.0, to: 0.0 };
+        let mut polyline = PolylineShape::default();
+        polyline.colors = vec![Color::WHITE, Color::RED];
+        let mut drawable = Drawable::Polyline(polyline);
+
+        assert!(!fade.apply(0.0, None, Some(&mut drawable)));
+        assert!(fade.apply(0.5, None, Some(&mut drawable)));
+        assert!(!fade.apply(0.5, None, Some(&mut drawable)));
+        assert!(matches!(
+            &drawable,
+            Drawable::Polyline(polyline) if polyline.colors.iter().all(|color| color.a == 0.5)
+        ));
+    }
+
+    #[test]
+    fn completed_tweens_report_and_call_back() {
+        let mut world = World::new();
+        let mut tessellator = Tessellator::new(0.1);
+        let calls = Arc::new(AtomicU32::new(0));
+
+        let mut tween = Tween::new(
+            TweenProperty::Size {
+                from: Vec2::splat(10.0),
+                to: Vec2::splat(20.0),
+            },
+            Duration::from_millis(100),
+            Easing::Linear,
+        );
+        tween.name = "grow".to_string();
+        let counter = Arc::clone(&calls);
+        tween.on_complete = Some(Box::new(move |_world, _entity| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+        let entity = world.spawn((
+            Transform::default(),
+            Drawable::Circle(CircleShape::default()),
+            Tweens::from(tween),
+        ));
+
+        let dt = Duration::from_millis(60);
+        assert!(update_tweens(&mut world, &mut tessellator, dt).is_empty());
+        let vertices = world.get::<&Drawable>(entity).unwrap().vertices().len();
+        assert!(vertices > 0);
+
+        let completed = update_tweens(&mut world, &mut tessellator, dt);
+        assert_eq!(
+            completed,
+            vec![TweenCompleted {
+                entity,
+                name: "grow".to_string()
+            }]
+        );
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert!(world.get::<&Tweens>(entity).is_err());
+        let drawable = world.get::<&Drawable>(entity).unwrap();
+        assert!(matches!(&*drawable, Drawable::Circle(circle) if circle.radius == 20.0));
+    }
+}